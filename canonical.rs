@@ -0,0 +1,519 @@
+// Formatação canônica dos arquivos de descrição, usada pelo subcomando fmt. A máquina é
+// reescrita no formato rotulado, com as seções sempre na mesma ordem, os conjuntos
+// ordenados e sem repetições, e as transições agrupadas pelo estado de origem e
+// ordenadas pelos símbolos lidos, com as setas alinhadas:
+//
+//     Q = {q0, q1}
+//     Sigma = {a}
+//     Gamma = {B, a}
+//     q0 = q0
+//     F = {q1}
+//
+//     delta:
+//         (q0, B) -> (q0, B, R)
+//         (q0, a) -> (q1, a, R)
+//
+//         (q1, a) -> (q1, a, R)
+//
+// Os comentários são mantidos junto da transição ou da seção a que pertencem. A ordem
+// das transições com o mesmo estado e os mesmos símbolos lidos é preservada, já que a
+// execução determinística usa a primeira delas; transições repetidas são removidas. Os
+// padrões de transição, com conjuntos, * e =, são mantidos como escritos, com os
+// conjuntos ordenados; dois padrões igualmente específicos que valem para um mesmo
+// símbolo também mantêm a ordem em que foram escritos. Os blocos for são mantidos,
+// depois das demais transições e na ordem em que foram escritos, com as suas transições
+// ordenadas da mesma forma; os estados gerados por eles não são escritos em Q.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::parser::{parse_with_comments, quote_if_needed, Comment, CommentAnchor, Description, ParseError, Section};
+use crate::pattern::{ReadPattern, TransitionPattern};
+use crate::tape::DEFAULT_BLANK;
+use crate::template::Template;
+use crate::TuringMachine;
+
+const INDENT: &str = "    ";
+
+// Compara dois nomes em ordem natural: os trechos numéricos são comparados pelo valor,
+// de modo que q2 vem antes de q10
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let digits = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut number = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        number.push(digit);
+                    }
+                    number
+                };
+                let (x, y) = (digits(&mut a), digits(&mut b));
+                let (x_value, y_value) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                let ordering = x_value
+                    .len()
+                    .cmp(&y_value.len())
+                    .then_with(|| x_value.cmp(y_value))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+// Conjunto em ordem natural e sem elementos repetidos
+fn canonical_set(items: &[String]) -> Vec<&str> {
+    let mut items: Vec<&str> = items.iter().map(String::as_str).collect();
+    items.sort_by(|a, b| natural_cmp(a, b));
+    items.dedup();
+    items
+}
+
+fn set_text(items: &[String]) -> String {
+    let items: Vec<_> = canonical_set(items).into_iter().map(quote_if_needed).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+// Transição com os conjuntos de símbolos lidos em ordem natural e sem repetições
+fn canonical_pattern(transition: &TransitionPattern) -> TransitionPattern {
+    let mut transition = transition.clone();
+    for read in &mut transition.read {
+        if let ReadPattern::Set(symbols) = read {
+            *symbols = canonical_set(symbols).into_iter().map(str::to_string).collect();
+        }
+    }
+    transition
+}
+
+// Ordena os itens, dados na ordem da descrição, pela comparação `cmp`, mas sem inverter
+// dois itens em conflito: a cada passo, escolhe o menor item cujos conflitos anteriores
+// já foram escolhidos. Como a ordem da descrição satisfaz as restrições, a ordem obtida
+// a partir da própria saída é a mesma.
+fn constrained_sort<T: Copy>(
+    items: &[T],
+    conflict: impl Fn(T, T) -> bool,
+    cmp: impl Fn(T, T) -> Ordering,
+) -> Vec<T> {
+    // Número de itens anteriores em conflito que ainda não foram escolhidos
+    let mut pending: Vec<usize> = (0..items.len())
+        .map(|i| (0..i).filter(|&j| conflict(items[j], items[i])).count())
+        .collect();
+    let mut chosen = vec![false; items.len()];
+    let mut order = Vec::with_capacity(items.len());
+    while let Some(next) = (0..items.len())
+        .filter(|&i| !chosen[i] && pending[i] == 0)
+        .min_by(|&i, &j| cmp(items[i], items[j]).then(i.cmp(&j)))
+    {
+        chosen[next] = true;
+        order.push(items[next]);
+        for i in next + 1..items.len() {
+            if conflict(items[next], items[i]) {
+                pending[i] -= 1;
+            }
+        }
+    }
+    order
+}
+
+// Parte da seção delta: uma transição fora dos blocos for ou um bloco for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DeltaItem {
+    Transition(usize),
+    Template(usize),
+}
+
+// Ordem canônica da seção delta. As transições fora dos blocos for vêm primeiro,
+// agrupadas pelo estado de origem e ordenadas pelos símbolos lidos, e os blocos, na ordem
+// da descrição, depois delas; as transições de cada bloco são ordenadas da mesma forma.
+// Duas transições igualmente específicas que geram um mesmo par (estado, símbolos lidos)
+// mantêm a ordem da descrição, já que a execução determinística usa a primeira delas;
+// pelo mesmo motivo, uma transição que vem depois de um bloco com o qual está em
+// conflito continua depois dele.
+fn delta_order(
+    tm: &TuringMachine,
+    transitions: &[TransitionPattern],
+    templates: &[Template],
+) -> Vec<(DeltaItem, Vec<usize>)> {
+    let template_of = |i: usize| templates.iter().find(|template| template.transitions.contains(&i));
+    let keys: Vec<HashSet<(String, Vec<String>)>> = transitions
+        .iter()
+        .enumerate()
+        .map(|(i, transition)| {
+            let instances = match template_of(i) {
+                Some(template) => template.instances(transition),
+                None => vec![transition.clone()],
+            };
+            instances
+                .iter()
+                .flat_map(|instance| instance.instances(&tm.tape_alphabet))
+                .map(|instance| (instance.from_state, instance.read_symbols))
+                .collect()
+        })
+        .collect();
+    let conflict = |i: usize, j: usize| {
+        transitions[i].specificity() == transitions[j].specificity() && !keys[i].is_disjoint(&keys[j])
+    };
+
+    let states = canonical_set(&tm.states);
+    let rank = |state: &str| states.iter().position(|&name| name == state).unwrap_or(usize::MAX);
+    let cmp = |i: usize, j: usize| {
+        let (a, b) = (&transitions[i], &transitions[j]);
+        rank(&a.from_state)
+            .cmp(&rank(&b.from_state))
+            .then_with(|| natural_cmp(&a.from_state, &b.from_state))
+            .then_with(|| a.read.cmp(&b.read))
+    };
+
+    // Itens na ordem da descrição; um bloco vazio fica na posição em que foi escrito
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i <= transitions.len() {
+        let starting = templates.iter().enumerate().filter(|(_, template)| template.transitions.start == i);
+        let empty = starting.clone().filter(|(_, template)| template.transitions.is_empty());
+        items.extend(empty.map(|(t, _)| DeltaItem::Template(t)));
+        match starting.clone().find(|(_, template)| !template.transitions.is_empty()) {
+            Some((t, template)) => {
+                items.push(DeltaItem::Template(t));
+                i = template.transitions.end;
+            }
+            None => {
+                if i < transitions.len() {
+                    items.push(DeltaItem::Transition(i));
+                }
+                i += 1;
+            }
+        }
+    }
+
+    let body = |t: usize| templates[t].transitions.clone();
+    let item_order = constrained_sort(
+        &items,
+        |a, b| match (a, b) {
+            (DeltaItem::Transition(i), DeltaItem::Transition(j)) => conflict(i, j),
+            (DeltaItem::Transition(i), DeltaItem::Template(t)) | (DeltaItem::Template(t), DeltaItem::Transition(i)) => {
+                body(t).any(|j| conflict(i, j))
+            }
+            (DeltaItem::Template(_), DeltaItem::Template(_)) => false,
+        },
+        |a, b| match (a, b) {
+            (DeltaItem::Transition(i), DeltaItem::Transition(j)) => cmp(i, j),
+            (DeltaItem::Transition(_), DeltaItem::Template(_)) => Ordering::Less,
+            (DeltaItem::Template(_), DeltaItem::Transition(_)) => Ordering::Greater,
+            (DeltaItem::Template(s), DeltaItem::Template(t)) => s.cmp(&t),
+        },
+    );
+
+    item_order
+        .into_iter()
+        .map(|item| match item {
+            DeltaItem::Transition(i) => (item, vec![i]),
+            DeltaItem::Template(t) => (item, constrained_sort(&body(t).collect::<Vec<_>>(), conflict, cmp)),
+        })
+        .collect()
+}
+
+// Transição mantida no lugar de cada uma: uma transição repetida é trocada pela sua
+// primeira ocorrência na ordem dada, entre as transições fora dos blocos for ou entre as
+// de um mesmo bloco
+fn replacements(transitions: &[TransitionPattern], order: &[(DeltaItem, Vec<usize>)]) -> Vec<usize> {
+    let mut replacement: Vec<usize> = (0..transitions.len()).collect();
+    let mut outside: Vec<usize> = Vec::new();
+    for (item, indices) in order {
+        let mut inside: Vec<usize> = Vec::new();
+        let kept = match item {
+            DeltaItem::Transition(_) => &mut outside,
+            DeltaItem::Template(_) => &mut inside,
+        };
+        for &i in indices {
+            match kept.iter().find(|&&other| transitions[other] == transitions[i]) {
+                Some(&other) => replacement[i] = other,
+                None => kept.push(i),
+            }
+        }
+    }
+    replacement
+}
+
+// Lado esquerdo de uma transição: (q0, a) ou, com k fitas, (q0, a1, ..., ak)
+fn left_side(transition: &TransitionPattern) -> String {
+    let mut text = format!("({}", quote_if_needed(&transition.from_state));
+    for read in &transition.read {
+        write!(text, ", {}", read).unwrap();
+    }
+    text.push(')');
+    text
+}
+
+// Lado direito de uma transição: (q1, b, R) ou (q1, b1, ..., bk, M1, ..., Mk)
+fn right_side(transition: &TransitionPattern) -> String {
+    let mut text = format!("({}", quote_if_needed(&transition.to_state));
+    for write in &transition.write {
+        write!(text, ", {}", write).unwrap();
+    }
+    for direction in &transition.moves {
+        write!(text, ", {}", direction).unwrap();
+    }
+    text.push(')');
+    text
+}
+
+// Escreve uma linha da descrição precedida dos comentários da mesma parte e seguida do
+// comentário de fim de linha, se houver
+fn write_item<'a>(output: &mut String, indent: &str, line: &str, comments: impl Iterator<Item = &'a Comment>) {
+    let mut trailing = None;
+    for comment in comments {
+        if comment.trailing && trailing.is_none() {
+            trailing = Some(&comment.text);
+        } else {
+            writeln!(output, "{}//{}", indent, comment.text).unwrap();
+        }
+    }
+    match trailing {
+        Some(text) => writeln!(output, "{}{} //{}", indent, line, text).unwrap(),
+        None => writeln!(output, "{}{}", indent, line).unwrap(),
+    }
+}
+
+// Escreve as transições na ordem dada, com as setas alinhadas e uma linha em branco
+// entre os grupos de transições de cada estado
+fn write_transitions<'a, I: Iterator<Item = &'a Comment>>(
+    output: &mut String,
+    indent: &str,
+    transitions: &[TransitionPattern],
+    order: &[usize],
+    attached: impl Fn(CommentAnchor) -> I,
+) {
+    let width = order.iter().map(|&i| left_side(&transitions[i]).chars().count()).max().unwrap_or(0);
+    for (position, &i) in order.iter().enumerate() {
+        let transition = &transitions[i];
+        if position > 0 && transitions[order[position - 1]].from_state != transition.from_state {
+            output.push('\n');
+        }
+        let line = format!("{:<width$} -> {}", left_side(transition), right_side(transition), width = width);
+        write_item(output, indent, &line, attached(CommentAnchor::Transition(i)));
+    }
+}
+
+// Cabeçalho de um bloco for: for x in {a, b}, y in {c} {
+fn template_header(template: &Template) -> String {
+    let bindings: Vec<String> = template
+        .bindings
+        .iter()
+        .map(|(variable, values)| format!("{} in {}", quote_if_needed(variable), set_text(values)))
+        .collect();
+    format!("for {} {{", bindings.join(", "))
+}
+
+// Escreve a máquina no formato canônico, com as transições e os blocos for como escritos
+// na descrição e os comentários nas suas posições
+fn format_with_comments(description: &Description) -> String {
+    let Description { machine: tm, templates, generated_states, comments, .. } = description;
+    let transitions: Vec<TransitionPattern> = description.transitions.iter().map(canonical_pattern).collect();
+    let order = delta_order(tm, &transitions, templates);
+    let replacement = &replacements(&transitions, &order);
+    let kept = |i: &usize| replacement[*i] == *i;
+    let attached = move |anchor: CommentAnchor| {
+        comments.iter().filter(move |comment| match (comment.anchor, anchor) {
+            // Os comentários de uma transição repetida passam para a que foi mantida
+            (CommentAnchor::Transition(i), CommentAnchor::Transition(kept)) => replacement[i] == kept,
+            (a, b) => a == b,
+        })
+    };
+    let has_comments = |anchor: CommentAnchor| attached(anchor).next().is_some();
+
+    let mut output = String::new();
+    for comment in attached(CommentAnchor::Header) {
+        writeln!(output, "//{}", comment.text).unwrap();
+    }
+    if has_comments(CommentAnchor::Header) {
+        output.push('\n');
+    }
+
+    let states: Vec<String> = tm.states.iter().filter(|state| !generated_states.contains(state)).cloned().collect();
+    let sections = [
+        (Section::States, Some(set_text(&states))),
+        (Section::InputAlphabet, Some(set_text(&tm.alphabet))),
+        (Section::TapeAlphabet, Some(set_text(&tm.tape_alphabet))),
+        (Section::InitialState, Some(quote_if_needed(&tm.initial_state).into_owned())),
+        (Section::FinalStates, Some(set_text(&tm.accept_states))),
+        (Section::RejectStates, (!tm.reject_states.is_empty()).then(|| set_text(&tm.reject_states))),
+        (Section::Blank, (tm.blank != DEFAULT_BLANK).then(|| quote_if_needed(&tm.blank).into_owned())),
+        (Section::Tapes, (tm.tapes != 1).then(|| tm.tapes.to_string())),
+    ];
+    for (section, value) in sections {
+        // Seções opcionais com o valor padrão só são escritas se tiverem comentários
+        let anchor = CommentAnchor::Section(section);
+        let value = match (section, value) {
+            (_, Some(value)) => value,
+            (Section::Blank, None) if has_comments(anchor) => tm.blank.clone(),
+            (Section::Tapes, None) if has_comments(anchor) => tm.tapes.to_string(),
+            (_, None) if has_comments(anchor) => "{}".to_string(),
+            (_, None) => continue,
+        };
+        write_item(&mut output, "", &format!("{} = {}", section.label(), value), attached(anchor));
+    }
+
+    output.push('\n');
+    write_item(&mut output, "", "delta:", attached(CommentAnchor::Section(Section::Transitions)));
+    // As transições seguidas fora dos blocos for são escritas juntas, e uma linha em
+    // branco separa cada bloco do que vem antes e depois dele
+    let mut position = 0;
+    while position < order.len() {
+        if position > 0 {
+            output.push('\n');
+        }
+        match order[position] {
+            (DeltaItem::Transition(_), _) => {
+                let run: Vec<usize> = order[position..]
+                    .iter()
+                    .take_while(|(item, _)| matches!(item, DeltaItem::Transition(_)))
+                    .flat_map(|(_, indices)| indices.iter().copied())
+                    .collect();
+                position += run.len();
+                let run: Vec<usize> = run.into_iter().filter(|i| kept(i)).collect();
+                write_transitions(&mut output, INDENT, &transitions, &run, attached);
+            }
+            (DeltaItem::Template(t), ref indices) => {
+                position += 1;
+                let body: Vec<usize> = indices.iter().copied().filter(|i| kept(i)).collect();
+                write_item(&mut output, INDENT, &template_header(&templates[t]), attached(CommentAnchor::Template(t)));
+                write_transitions(&mut output, &INDENT.repeat(2), &transitions, &body, attached);
+                writeln!(output, "{}}}", INDENT).unwrap();
+            }
+        }
+    }
+
+    for comment in attached(CommentAnchor::End) {
+        writeln!(output, "//{}", comment.text).unwrap();
+    }
+    output
+}
+
+// Função para escrever a máquina no formato canônico
+pub fn format_machine(tm: &TuringMachine) -> String {
+    format_with_comments(&Description {
+        machine: tm.clone(),
+        transitions: tm.transitions.iter().map(TransitionPattern::from).collect(),
+        templates: Vec::new(),
+        generated_states: Vec::new(),
+        comments: Vec::new(),
+    })
+}
+
+// Função para formatar o texto de uma descrição, mantendo os seus comentários
+pub fn format_description(source: &str, file: &str) -> Result<String, Vec<ParseError>> {
+    Ok(format_with_comments(&parse_with_comments(source, file)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_description, run, RunOptions, Verdict};
+
+    // Veredito da máquina descrita para cada palavra
+    fn verdicts(source: &str, words: &[&str]) -> Vec<Verdict> {
+        let tm = parse_description(source, "test.txt").unwrap();
+        words.iter().map(|word| run(&tm, word, &RunOptions::default()).unwrap().verdict).collect()
+    }
+
+    #[test]
+    fn overlapping_sets_keep_their_order() {
+        let source = "Q = {s, q0, q1, q2}\nSigma = {a, b, c}\nGamma = {a, b, c, B}\nq0 = s\nF = {q1}\n\
+                      delta:\n    (s, B) -> (q0, B, R)\n    (q0, {b, c}) -> (q1, =, R)\n    (q0, {a, b}) -> (q2, =, R)\n";
+        let formatted = format_description(source, "test.txt").unwrap();
+        let words = ["a", "b", "c"];
+        assert_eq!(verdicts(source, &words), verdicts(&formatted, &words));
+        assert_eq!(verdicts(&formatted, &["b"]), vec![Verdict::Accepted]);
+        assert_eq!(format_description(&formatted, "test.txt").unwrap(), formatted);
+    }
+
+    #[test]
+    fn transition_after_overlapping_template_stays_after_it() {
+        let source = "Q = {p, q, r}\nSigma = {a, b}\nGamma = {a, b, B}\nq0 = q\nF = {r}\ndelta:\n\
+                      for x in {a} {\n    (q, {x, b}) -> (r, =, R)\n}\n(q, {a, B}) -> (p, =, R)\n(q, b) -> (r, b, S)\n";
+        let formatted = format_description(source, "test.txt").unwrap();
+        let words = ["", "a", "b", "ab"];
+        assert_eq!(verdicts(source, &words), verdicts(&formatted, &words));
+        // A ordem só importa entre as transições com o mesmo estado e os mesmos símbolos
+        let expanded = |source: &str| {
+            let mut transitions = parse_description(source, "test.txt").unwrap().transitions;
+            transitions.sort_by(|a, b| a.read_symbols.cmp(&b.read_symbols));
+            transitions
+        };
+        assert_eq!(expanded(source), expanded(&formatted));
+        assert_eq!(format_description(&formatted, "test.txt").unwrap(), formatted);
+    }
+
+    #[test]
+    fn formatting_is_idempotent_and_keeps_comments() {
+        let source = "// máquina de teste\nF = {q1}\nq0 = q0\nGamma = {B, a, \"X 1\"}\nSigma = {a}\nQ = {q10, q2, q1, q0}\n\
+                      delta:\n(q2, a) -> (q1, a, R) // fim\n(q0, a) -> (q2, \"X 1\", R)\n(q0, a) -> (q2, \"X 1\", R)\n\
+                      (q0, *) -> (q2, =, R)\n";
+        let formatted = format_description(source, "test.txt").unwrap();
+        assert_eq!(
+            formatted,
+            "// máquina de teste\n\nQ = {q0, q1, q2, q10}\nSigma = {a}\nGamma = {B, \"X 1\", a}\nq0 = q0\nF = {q1}\n\n\
+             delta:\n    (q0, a) -> (q2, \"X 1\", R)\n    (q0, *) -> (q2, =, R)\n\n    (q2, a) -> (q1, a, R) // fim\n"
+        );
+        assert_eq!(format_description(&formatted, "test.txt").unwrap(), formatted);
+        let words = ["", "a", "aa", "aaa"];
+        assert_eq!(verdicts(source, &words), verdicts(&formatted, &words));
+    }
+
+    #[test]
+    fn templates_are_kept_and_generated_states_left_out_of_q() {
+        let source = "Q = {s, q0, back}\nSigma = {a, b}\nGamma = {a, b, B}\nq0 = s\nF = {back}\ndelta:\n\
+                      (s, B) -> (q0, B, R)\nfor x in {b, a} {\n(q0, x) -> (carry[x], B, R)\n\
+                      (carry[x], *) -> (carry[x], =, R)\n(carry[x], B) -> (back, x, L)\n}\n";
+        let formatted = format_description(source, "test.txt").unwrap();
+        assert!(formatted.starts_with("Q = {back, q0, s}\n"));
+        assert!(formatted.contains("    for x in {a, b} {\n        (q0, x)       -> (carry[x], B, R)\n"));
+        assert_eq!(format_description(&formatted, "test.txt").unwrap(), formatted);
+        let words = ["", "a", "ab", "ba", "bba"];
+        assert_eq!(verdicts(source, &words), verdicts(&formatted, &words));
+    }
+
+    #[test]
+    fn formatted_machine_reads_back_with_quoted_and_multi_tape_transitions() {
+        let sources = [
+            "Q = {q0, \"q fim\"}\nSigma = {a, X1}\nGamma = {a, X1, \",\", \"*\", B}\nq0 = q0\nF = {\"q fim\"}\ndelta:\n\
+             (q0, X1) -> (q0, \",\", R)\n(q0, \"*\") -> (\"q fim\", \"*\", S)\n(q0, a) -> (q0, \"*\", L)\n",
+            "Q = {q0, q1}\nSigma = {a}\nGamma = {a, #a, B}\nq0 = q0\nF = {q1}\ndelta:\n\
+             (q0, a, B) -> (q0, a, #a, R, R)\n(q0, B, B) -> (q1, B, B, S, L)\n",
+        ];
+        for source in sources {
+            let tm = parse_description(source, "test.txt").unwrap();
+            let formatted = format_machine(&tm);
+            let read = parse_description(&formatted, "test.txt").unwrap();
+            let sorted = |tm: &TuringMachine| {
+                let mut transitions = tm.transitions.clone();
+                transitions.sort_by(|a, b| (&a.from_state, &a.read_symbols).cmp(&(&b.from_state, &b.read_symbols)));
+                transitions
+            };
+            assert_eq!(sorted(&read), sorted(&tm));
+            assert_eq!(format_description(&formatted, "test.txt").unwrap(), formatted);
+        }
+    }
+
+    #[test]
+    fn natural_order_compares_numbers_by_value() {
+        assert_eq!(natural_cmp("q2", "q10"), Ordering::Less);
+        assert_eq!(natural_cmp("q10", "q010"), Ordering::Less);
+        assert_eq!(natural_cmp("a", "b"), Ordering::Less);
+    }
+}
@@ -0,0 +1,321 @@
+// Representação compilada de uma máquina de Turing, usada durante a execução. Estados
+// e símbolos são convertidos em identificadores numéricos e, nas máquinas de uma fita,
+// as transições ficam em uma tabela densa indexada por (estado, símbolo lido), de modo
+// que cada passo da execução é uma consulta em tempo constante, sem comparações de
+// strings nem alocações. Com k fitas e n símbolos, a tabela densa teria n^k posições por
+// estado; por isso, as máquinas de várias fitas, e as de uma fita cuja tabela passaria
+// de DENSE_TABLE_LIMIT posições, usam uma tabela esparsa, com apenas os pares (estado,
+// símbolos lidos) que têm transições. A tabela esparsa é consultada com os símbolos
+// lidos emprestados, também sem alocações.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::tape::Tape;
+use crate::{Move, TuringMachine};
+
+pub type StateId = usize;
+pub type SymbolId = usize;
+
+// Maior número de posições da tabela densa
+const DENSE_TABLE_LIMIT: usize = 1 << 22;
+
+// Transição com o estado de destino e os símbolos escritos já convertidos em
+// identificadores, um por fita
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledTransition {
+    pub to_state: StateId,
+    pub write_symbols: Vec<SymbolId>,
+    pub moves: Vec<Move>,
+}
+
+impl CompiledTransition {
+    // Escreve um símbolo em cada fita e move a sua cabeça de leitura/escrita
+    pub fn apply(&self, tapes: &mut [Tape<SymbolId>]) {
+        for ((tape, &symbol), direction) in tapes.iter_mut().zip(&self.write_symbols).zip(&self.moves) {
+            tape.write(symbol);
+            match direction {
+                Move::Right => tape.move_right(),
+                Move::Left => tape.move_left(),
+                Move::Stay => {}
+            }
+        }
+    }
+}
+
+pub struct CompiledMachine {
+    state_names: Vec<String>,
+    symbols: Vec<String>,
+    symbol_ids: HashMap<String, SymbolId>,
+    // Transições agrupadas por (estado, símbolos lidos), na ordem da descrição dentro
+    // de cada grupo
+    transitions: Vec<CompiledTransition>,
+    // Intervalo das transições de cada par (estado, símbolos lidos)
+    table: TransitionTable,
+    tapes: usize,
+    accepting: Vec<bool>,
+    rejecting: Vec<bool>,
+    initial_state: StateId,
+    blank: SymbolId,
+}
+
+// Tabela de transições
+enum TransitionTable {
+    // A linha de cada estado tem uma posição para cada símbolo lido, com o intervalo das
+    // transições correspondentes
+    Dense(Vec<Range<usize>>),
+    // Para cada estado, o intervalo das transições de cada sequência de símbolos lidos
+    Sparse(HashMap<StateId, HashMap<Vec<SymbolId>, Range<usize>>>),
+}
+
+// Agrupa as transições com a mesma chave, mantendo a ordem da descrição dentro de cada
+// grupo, e devolve as transições agrupadas junto com o intervalo de cada grupo
+fn group<K: Clone + Eq + Hash>(
+    entries: Vec<(K, CompiledTransition)>,
+) -> (Vec<CompiledTransition>, Vec<(K, Range<usize>)>) {
+    let mut indices: HashMap<K, usize> = HashMap::new();
+    let mut groups: Vec<(K, Vec<CompiledTransition>)> = Vec::new();
+    for (key, compiled) in entries {
+        let index = *indices.entry(key.clone()).or_insert_with(|| {
+            groups.push((key, Vec::new()));
+            groups.len() - 1
+        });
+        groups[index].1.push(compiled);
+    }
+
+    let mut transitions = Vec::new();
+    let mut ranges = Vec::with_capacity(groups.len());
+    for (key, group) in groups {
+        let start = transitions.len();
+        transitions.extend(group);
+        ranges.push((key, start..transitions.len()));
+    }
+    (transitions, ranges)
+}
+
+// Associa nomes a identificadores numéricos consecutivos
+struct Interner<T> {
+    names: Vec<T>,
+    ids: HashMap<T, usize>,
+}
+
+impl<T: Clone + Eq + std::hash::Hash> Interner<T> {
+    fn new() -> Self {
+        Interner { names: Vec::new(), ids: HashMap::new() }
+    }
+
+    fn intern(&mut self, name: &T) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.clone());
+        self.ids.insert(name.clone(), id);
+        id
+    }
+}
+
+impl CompiledMachine {
+    // Compila a máquina de Turing. Quando há mais de uma transição para o mesmo par
+    // (estado, símbolos lidos), todas são guardadas, mas a execução determinística usa
+    // apenas a primeira, como na busca linear pelas transições.
+    pub fn compile(tm: &TuringMachine) -> CompiledMachine {
+        let mut states = Interner::new();
+        let initial_state = states.intern(&tm.initial_state);
+        for state in tm.states.iter().chain(&tm.accept_states).chain(&tm.reject_states) {
+            states.intern(state);
+        }
+
+        let mut symbols = Interner::new();
+        let blank = symbols.intern(&tm.blank);
+        for symbol in tm.tape_alphabet.iter().chain(&tm.alphabet) {
+            symbols.intern(symbol);
+        }
+
+        let mut entries = Vec::new();
+        for transition in &tm.transitions {
+            let from_state = states.intern(&transition.from_state);
+            let read_symbols: Vec<SymbolId> =
+                transition.read_symbols.iter().map(|symbol| symbols.intern(symbol)).collect();
+            let compiled = CompiledTransition {
+                to_state: states.intern(&transition.to_state),
+                write_symbols: transition.write_symbols.iter().map(|symbol| symbols.intern(symbol)).collect(),
+                moves: transition.moves.clone(),
+            };
+            entries.push((from_state, read_symbols, compiled));
+        }
+
+        let symbol_count = symbols.names.len();
+        let dense_size = states.names.len().checked_mul(symbol_count).filter(|&size| size <= DENSE_TABLE_LIMIT);
+        let (transitions, table) = match dense_size {
+            Some(size) if tm.tapes == 1 => {
+                let entries = entries
+                    .into_iter()
+                    .map(|(from_state, read_symbols, compiled)| (from_state * symbol_count + read_symbols[0], compiled));
+                let (transitions, ranges) = group(entries.collect());
+                let mut table = vec![0..0; size];
+                for (position, range) in ranges {
+                    table[position] = range;
+                }
+                (transitions, TransitionTable::Dense(table))
+            }
+            _ => {
+                let entries = entries
+                    .into_iter()
+                    .map(|(from_state, read_symbols, compiled)| ((from_state, read_symbols), compiled));
+                let (transitions, ranges) = group(entries.collect());
+                let mut table: HashMap<StateId, HashMap<Vec<SymbolId>, Range<usize>>> = HashMap::new();
+                for ((from_state, read_symbols), range) in ranges {
+                    table.entry(from_state).or_default().insert(read_symbols, range);
+                }
+                (transitions, TransitionTable::Sparse(table))
+            }
+        };
+
+        let accepting = states.names.iter().map(|state| tm.accept_states.contains(state)).collect();
+        let rejecting = states.names.iter().map(|state| tm.reject_states.contains(state)).collect();
+
+        CompiledMachine {
+            state_names: states.names,
+            symbols: symbols.names,
+            symbol_ids: symbols.ids,
+            transitions,
+            table,
+            tapes: tm.tapes,
+            accepting,
+            rejecting,
+            initial_state,
+            blank,
+        }
+    }
+
+    // Intervalo das transições a partir do estado lendo os símbolos, um por fita
+    fn range(&self, state: StateId, symbols: &[SymbolId]) -> Range<usize> {
+        match &self.table {
+            TransitionTable::Dense(table) => table[state * self.symbols.len() + symbols[0]].clone(),
+            TransitionTable::Sparse(table) => {
+                table.get(&state).and_then(|row| row.get(symbols)).cloned().unwrap_or(0..0)
+            }
+        }
+    }
+
+    // Primeira transição a partir do estado lendo os símbolos, um por fita, se houver
+    pub fn transition(&self, state: StateId, symbols: &[SymbolId]) -> Option<&CompiledTransition> {
+        self.transitions(state, symbols).first()
+    }
+
+    // Todas as transições a partir do estado lendo os símbolos, na ordem da descrição
+    pub fn transitions(&self, state: StateId, symbols: &[SymbolId]) -> &[CompiledTransition] {
+        &self.transitions[self.range(state, symbols)]
+    }
+
+    // Índice da primeira transição a partir do estado lendo os símbolos, para quem
+    // guarda o resultado da consulta entre um passo e outro
+    pub(crate) fn first_transition(&self, state: StateId, symbols: &[SymbolId]) -> Option<usize> {
+        let range = self.range(state, symbols);
+        (!range.is_empty()).then_some(range.start)
+    }
+
+    pub(crate) fn transition_at(&self, index: usize) -> &CompiledTransition {
+        &self.transitions[index]
+    }
+
+    pub fn tapes(&self) -> usize {
+        self.tapes
+    }
+
+    pub fn is_accepting(&self, state: StateId) -> bool {
+        self.accepting[state]
+    }
+
+    pub fn is_rejecting(&self, state: StateId) -> bool {
+        self.rejecting[state]
+    }
+
+    pub fn initial_state(&self) -> StateId {
+        self.initial_state
+    }
+
+    pub fn blank(&self) -> SymbolId {
+        self.blank
+    }
+
+    pub fn state_name(&self, state: StateId) -> &str {
+        &self.state_names[state]
+    }
+
+    pub fn symbol(&self, symbol: SymbolId) -> &str {
+        &self.symbols[symbol]
+    }
+
+    // Identificador do símbolo, se ele aparecer em algum alfabeto ou transição
+    pub fn symbol_id(&self, symbol: &str) -> Option<SymbolId> {
+        self.symbol_ids.get(symbol).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Transition;
+
+    // Máquina de k fitas com um alfabeto de n símbolos e uma única transição
+    fn machine(tapes: usize, symbols: usize) -> TuringMachine {
+        let mut tape_alphabet: Vec<String> = (0..symbols - 1).map(|i| format!("s{}", i)).collect();
+        tape_alphabet.push("B".to_string());
+        TuringMachine {
+            states: vec!["q0".to_string(), "q1".to_string()],
+            alphabet: vec!["s0".to_string()],
+            tape_alphabet,
+            transitions: vec![Transition {
+                from_state: "q0".to_string(),
+                read_symbols: vec!["B".to_string(); tapes],
+                to_state: "q1".to_string(),
+                write_symbols: vec!["s0".to_string(); tapes],
+                moves: vec![Move::Right; tapes],
+            }],
+            initial_state: "q0".to_string(),
+            accept_states: vec!["q1".to_string()],
+            reject_states: Vec::new(),
+            blank: "B".to_string(),
+            tapes,
+        }
+    }
+
+    #[test]
+    fn single_tape_machines_use_the_dense_table() {
+        let compiled = CompiledMachine::compile(&machine(1, 3));
+        assert!(matches!(compiled.table, TransitionTable::Dense(_)));
+        let blank = compiled.blank();
+        assert_eq!(compiled.transition(compiled.initial_state(), &[blank]).map(|t| t.to_state), Some(1));
+        let symbol = compiled.symbol_id("s0").unwrap();
+        assert!(compiled.transition(compiled.initial_state(), &[symbol]).is_none());
+    }
+
+    #[test]
+    fn large_multi_tape_machines_use_a_sparse_table() {
+        // Uma tabela densa teria 40^100 posições por estado
+        for tapes in [2, 40, 100] {
+            let compiled = CompiledMachine::compile(&machine(tapes, 40));
+            assert!(matches!(compiled.table, TransitionTable::Sparse(_)));
+            let blank = compiled.blank();
+            let transitions = compiled.transitions(compiled.initial_state(), &vec![blank; tapes]);
+            assert_eq!(transitions.len(), 1);
+            assert_eq!(transitions[0].write_symbols, vec![compiled.symbol_id("s0").unwrap(); tapes]);
+            assert!(compiled.transitions(1, &vec![blank; tapes]).is_empty());
+        }
+    }
+
+    #[test]
+    fn repeated_pairs_keep_the_description_order() {
+        let mut tm = machine(2, 3);
+        let mut second = tm.transitions[0].clone();
+        second.to_state = "q0".to_string();
+        tm.transitions.push(second);
+        let compiled = CompiledMachine::compile(&tm);
+        let blank = compiled.blank();
+        let targets: Vec<StateId> = compiled.transitions(0, &[blank, blank]).iter().map(|t| t.to_state).collect();
+        assert_eq!(targets, vec![1, 0]);
+    }
+}
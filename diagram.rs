@@ -0,0 +1,352 @@
+// Diagramas de estados de uma máquina de Turing, nos formatos DOT (Graphviz), Mermaid
+// e PlantUML. Em todos eles, as transições entre o mesmo par de estados são reunidas
+// em uma única aresta, identificada por um rótulo por transição no formato a→X,R
+// (símbolo lido, símbolo escrito e movimento).
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use crate::{Transition, TuringMachine};
+
+// Formatos de diagrama disponíveis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagramFormat {
+    Dot,
+    Mermaid,
+    PlantUml,
+}
+
+impl DiagramFormat {
+    pub fn from_name(name: &str) -> Option<DiagramFormat> {
+        match name {
+            "dot" => Some(DiagramFormat::Dot),
+            "mermaid" => Some(DiagramFormat::Mermaid),
+            "plantuml" => Some(DiagramFormat::PlantUml),
+            _ => None,
+        }
+    }
+
+    // Gera o diagrama de estados da máquina neste formato
+    pub fn render(self, tm: &TuringMachine) -> String {
+        match self {
+            DiagramFormat::Dot => to_dot(tm),
+            DiagramFormat::Mermaid => to_mermaid(tm),
+            DiagramFormat::PlantUml => to_plantuml(tm),
+        }
+    }
+}
+
+// Aresta do diagrama, com os rótulos das transições do estado de origem para o de destino
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge<'a> {
+    pub from_state: &'a str,
+    pub to_state: &'a str,
+    pub labels: Vec<String>,
+}
+
+// Rótulo de uma transição no diagrama. Com mais de uma fita, os símbolos e movimentos de
+// cada fita são separados por vírgulas: a,B→a,a,R,R.
+pub fn transition_label(transition: &Transition) -> String {
+    let moves: Vec<String> = transition.moves.iter().map(|direction| direction.to_string()).collect();
+    format!("{}→{},{}", transition.read_symbols.join(","), transition.write_symbols.join(","), moves.join(","))
+}
+
+// Função que reúne as transições por par de estados, na ordem em que cada par aparece
+// na descrição
+pub fn merged_edges(tm: &TuringMachine) -> Vec<Edge<'_>> {
+    let mut edges: Vec<Edge> = Vec::new();
+    for transition in &tm.transitions {
+        let label = transition_label(transition);
+        let existing = edges
+            .iter_mut()
+            .find(|edge| edge.from_state == transition.from_state && edge.to_state == transition.to_state);
+        match existing {
+            Some(edge) => edge.labels.push(label),
+            None => edges.push(Edge {
+                from_state: &transition.from_state,
+                to_state: &transition.to_state,
+                labels: vec![label],
+            }),
+        }
+    }
+    edges
+}
+
+// Função que distribui os estados em camadas, pela distância a partir do estado
+// inicial em uma busca em largura. Estados inalcançáveis ficam em uma última camada.
+pub(crate) fn bfs_layers(tm: &TuringMachine) -> Vec<Vec<&str>> {
+    let mut states: Vec<&str> = Vec::new();
+    let referenced = tm.transitions.iter().flat_map(|transition| [&transition.from_state, &transition.to_state]);
+    for state in [&tm.initial_state].into_iter().chain(&tm.states).chain(&tm.accept_states).chain(referenced) {
+        if !states.contains(&state.as_str()) {
+            states.push(state);
+        }
+    }
+
+    let mut layer_of: Vec<Option<usize>> = vec![None; states.len()];
+    layer_of[0] = Some(0);
+    let mut queue = VecDeque::from([0]);
+    while let Some(index) = queue.pop_front() {
+        let layer = layer_of[index].unwrap();
+        for transition in tm.transitions.iter().filter(|transition| transition.from_state == states[index]) {
+            let next = states.iter().position(|&state| state == transition.to_state).unwrap();
+            if layer_of[next].is_none() {
+                layer_of[next] = Some(layer + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let reachable_layers = layer_of.iter().flatten().max().map_or(0, |&layer| layer + 1);
+    let mut layers = vec![Vec::new(); reachable_layers];
+    for (state, layer) in states.into_iter().zip(layer_of) {
+        match layer {
+            Some(layer) => layers[layer].push(state),
+            None => {
+                if layers.len() == reachable_layers {
+                    layers.push(Vec::new());
+                }
+                layers[reachable_layers].push(state);
+            }
+        }
+    }
+    layers
+}
+
+// Escapa um texto para uso entre aspas no formato DOT
+pub(crate) fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Função para exportar o diagrama de estados no formato DOT do Graphviz. O estado
+// inicial recebe uma seta de entrada e os estados finais são desenhados com círculo
+// duplo.
+pub fn to_dot(tm: &TuringMachine) -> String {
+    let mut output = String::new();
+    output.push_str("digraph turing_machine {\n");
+    output.push_str("    rankdir=LR;\n");
+    output.push_str("    node [shape=circle];\n");
+    output.push_str("    __start [shape=point, style=invis];\n");
+
+    for state in &tm.states {
+        let shape = if tm.accept_states.contains(state) { "doublecircle" } else { "circle" };
+        writeln!(output, "    \"{}\" [shape={}];", escape_dot(state), shape).unwrap();
+    }
+    writeln!(output, "    __start -> \"{}\";", escape_dot(&tm.initial_state)).unwrap();
+
+    for edge in merged_edges(tm) {
+        let labels: Vec<String> = edge.labels.iter().map(|label| escape_dot(label)).collect();
+        writeln!(
+            output,
+            "    \"{}\" -> \"{}\" [label=\"{}\"];",
+            escape_dot(edge.from_state),
+            escape_dot(edge.to_state),
+            labels.join("\\n")
+        )
+        .unwrap();
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+// Identificadores dos estados nos formatos Mermaid e PlantUML, que aceitam apenas
+// letras, dígitos e '_'. Os estados com outros caracteres recebem um identificador
+// s0, s1, ... e são declarados com o nome original como descrição.
+struct StateIds<'a> {
+    names: Vec<&'a str>,
+    ids: Vec<String>,
+}
+
+impl<'a> StateIds<'a> {
+    fn new(tm: &'a TuringMachine) -> StateIds<'a> {
+        let mut names: Vec<&str> = Vec::new();
+        let referenced = tm.transitions.iter().flat_map(|transition| [&transition.from_state, &transition.to_state]);
+        let states = tm.states.iter().chain([&tm.initial_state]).chain(&tm.accept_states);
+        for state in states.chain(referenced) {
+            if !names.contains(&state.as_str()) {
+                names.push(state);
+            }
+        }
+        let mut ids: Vec<String> = Vec::new();
+        let mut next_alias = 0;
+        for name in &names {
+            let simple = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if simple {
+                ids.push(name.to_string());
+                continue;
+            }
+            // O identificador não pode coincidir com o nome de outro estado
+            let mut alias = format!("s{}", next_alias);
+            while names.contains(&alias.as_str()) || ids.contains(&alias) {
+                next_alias += 1;
+                alias = format!("s{}", next_alias);
+            }
+            next_alias += 1;
+            ids.push(alias);
+        }
+        StateIds { names, ids }
+    }
+
+    fn id(&self, state: &str) -> &str {
+        let index = self.names.iter().position(|&name| name == state).expect("every state is collected");
+        &self.ids[index]
+    }
+
+    // Estados cujo identificador difere do nome, que precisam ser declarados
+    fn aliased(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.names.iter().zip(&self.ids).filter(|(name, id)| **name != id.as_str()).map(|(name, id)| (*name, id.as_str()))
+    }
+}
+
+// Função para exportar o diagrama de estados como um stateDiagram-v2 do Mermaid. O
+// estado inicial sai do pseudoestado [*] e os estados finais levam a ele.
+pub fn to_mermaid(tm: &TuringMachine) -> String {
+    let ids = StateIds::new(tm);
+    let mut output = String::new();
+    output.push_str("stateDiagram-v2\n");
+
+    for (name, id) in ids.aliased() {
+        writeln!(output, "    state \"{}\" as {}", escape_mermaid(name), id).unwrap();
+    }
+    writeln!(output, "    [*] --> {}", ids.id(&tm.initial_state)).unwrap();
+    for edge in merged_edges(tm) {
+        let labels: Vec<String> = edge.labels.iter().map(|label| escape_mermaid(label)).collect();
+        writeln!(output, "    {} --> {} : {}", ids.id(edge.from_state), ids.id(edge.to_state), labels.join("<br/>"))
+            .unwrap();
+    }
+    for state in &tm.accept_states {
+        writeln!(output, "    {} --> [*]", ids.id(state)).unwrap();
+    }
+    output
+}
+
+// Escapa os caracteres com significado especial nos rótulos e nomes de estados do
+// Mermaid, escrevendo-os como códigos de entidade #NN;
+fn escape_mermaid(text: &str) -> String {
+    let mut escaped = String::new();
+    for symbol in text.chars() {
+        match symbol {
+            ':' | ';' | '#' | '<' | '>' | '"' => write!(escaped, "#{};", symbol as u32).unwrap(),
+            _ => escaped.push(symbol),
+        }
+    }
+    escaped
+}
+
+// Escapa um texto para os rótulos e nomes de estados do PlantUML. A barra invertida é
+// dobrada, para que um símbolo como \n não vire uma quebra de linha, e as aspas e os
+// caracteres da marcação creole são escritos como entidades &#NN;.
+fn escape_plantuml(text: &str) -> String {
+    let mut escaped = String::new();
+    for symbol in text.chars() {
+        match symbol {
+            '\\' => escaped.push_str("\\\\"),
+            '"' | '&' | '<' | '>' | '~' | '*' | '/' | '_' | '-' | '=' | '^' | '|' | '#' => {
+                write!(escaped, "&#{};", symbol as u32).unwrap()
+            }
+            _ => escaped.push(symbol),
+        }
+    }
+    escaped
+}
+
+// Função para exportar o diagrama de estados no formato PlantUML, com as mesmas
+// convenções do Mermaid para os estados inicial e finais
+pub fn to_plantuml(tm: &TuringMachine) -> String {
+    let ids = StateIds::new(tm);
+    let mut output = String::new();
+    output.push_str("@startuml\n");
+    output.push_str("hide empty description\n");
+
+    for (name, id) in ids.aliased() {
+        writeln!(output, "state \"{}\" as {}", escape_plantuml(name), id).unwrap();
+    }
+    writeln!(output, "[*] --> {}", ids.id(&tm.initial_state)).unwrap();
+    for edge in merged_edges(tm) {
+        let labels: Vec<String> = edge.labels.iter().map(|label| escape_plantuml(label)).collect();
+        writeln!(output, "{} --> {} : {}", ids.id(edge.from_state), ids.id(edge.to_state), labels.join("\\n")).unwrap();
+    }
+    for state in &tm.accept_states {
+        writeln!(output, "{} --> [*]", ids.id(state)).unwrap();
+    }
+    output.push_str("@enduml\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+
+    // Máquina com aspas, barra invertida e caracteres especiais nos nomes e símbolos
+    fn machine() -> TuringMachine {
+        let source = "Q = {q0, \"q fim\", \"a\\\"b\"}\nSigma = {a, \"\\\\\"}\n\
+                      Gamma = {a, \"\\\\\", \"\\\"\", \"*\", \":\", \"_\", B}\nq0 = q0\nF = {\"q fim\"}\ndelta:\n\
+                      (q0, a) -> (\"q fim\", \"*\", R)\n(q0, \"\\\\\") -> (\"a\\\"b\", \"\\\"\", L)\n\
+                      (\"a\\\"b\", \":\") -> (\"a\\\"b\", \"_\", S)\n(q0, B) -> (q0, B, R)\n\
+                      (q0, \"*\") -> (q0, a, R)\n";
+        parse_description(source, "test.txt").unwrap()
+    }
+
+    #[test]
+    fn dot_escapes_quotes_and_backslashes() {
+        let expected = r#"digraph turing_machine {
+    rankdir=LR;
+    node [shape=circle];
+    __start [shape=point, style=invis];
+    "q0" [shape=circle];
+    "q fim" [shape=doublecircle];
+    "a\"b" [shape=circle];
+    __start -> "q0";
+    "q0" -> "q fim" [label="a→*,R"];
+    "q0" -> "a\"b" [label="\\→\",L"];
+    "a\"b" -> "a\"b" [label=":→_,S"];
+    "q0" -> "q0" [label="B→B,R\n*→a,R"];
+}
+"#;
+        assert_eq!(to_dot(&machine()), expected);
+    }
+
+    #[test]
+    fn mermaid_writes_special_characters_as_entity_codes() {
+        let expected = r#"stateDiagram-v2
+    state "q fim" as s0
+    state "a#34;b" as s1
+    [*] --> q0
+    q0 --> s0 : a→*,R
+    q0 --> s1 : \→#34;,L
+    s1 --> s1 : #58;→_,S
+    q0 --> q0 : B→B,R<br/>*→a,R
+    s0 --> [*]
+"#;
+        assert_eq!(to_mermaid(&machine()), expected);
+    }
+
+    #[test]
+    fn plantuml_escapes_backslashes_and_creole_markup() {
+        let expected = r#"@startuml
+hide empty description
+state "q fim" as s0
+state "a&#34;b" as s1
+[*] --> q0
+q0 --> s0 : a→&#42;,R
+q0 --> s1 : \\→&#34;,L
+s1 --> s1 : :→&#95;,S
+q0 --> q0 : B→B,R\n&#42;→a,R
+s0 --> [*]
+@enduml
+"#;
+        assert_eq!(to_plantuml(&machine()), expected);
+    }
+
+    #[test]
+    fn state_ids_do_not_collide_with_state_names() {
+        let source = "Q = {s0, \"q 1\"}\nSigma = {a}\nGamma = {a, B}\nq0 = s0\nF = {\"q 1\"}\ndelta:\n\
+                      (s0, a) -> (\"q 1\", a, R)\n";
+        let tm = parse_description(source, "test.txt").unwrap();
+        let ids = StateIds::new(&tm);
+        assert_eq!(ids.id("s0"), "s0");
+        assert_eq!(ids.id("q 1"), "s1");
+    }
+}
@@ -0,0 +1,596 @@
+// Formatos de arquivo em que uma máquina de Turing pode ser descrita: o formato próprio
+// do arquivo de descrição, JSON, TOML e o XML do JFLAP. JSON e TOML compartilham a
+// mesma estrutura, lida para um `Value` e convertida em uma `TuringMachine`:
+//
+//     {
+//       "states": ["q0", "q1"],
+//       "input_alphabet": ["a"],
+//       "tape_alphabet": ["a", "B"],
+//       "blank": "B",
+//       "initial_state": "q0",
+//       "final_states": ["q1"],
+//       "reject_states": [],
+//       "tapes": 1,
+//       "transitions": [
+//         {"from": "q0", "read": "a", "to": "q1", "write": "a", "move": "R"}
+//       ]
+//     }
+//
+// Em uma máquina de várias fitas, read, write e move são listas com um elemento por
+// fita. Os campos blank, reject_states e tapes são opcionais; os demais, obrigatórios.
+// Nomes de estados e símbolos não podem ser vazios, e os conjuntos não podem ter
+// elementos repetidos.
+
+use std::fmt;
+
+use crate::tape::DEFAULT_BLANK;
+use crate::{Move, Transition, TuringMachine};
+
+// Maior número de fitas aceito no campo tapes. Sem transições, nada mais limita o número
+// de fitas, e cada uma é criada no início de toda execução.
+pub const MAX_TAPES: usize = 256;
+
+// Formatos de arquivo de máquina reconhecidos
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MachineFormat {
+    Description,
+    Json,
+    Toml,
+    Jflap,
+}
+
+impl MachineFormat {
+    pub fn from_name(name: &str) -> Option<MachineFormat> {
+        match name {
+            "description" | "desc" | "txt" => Some(MachineFormat::Description),
+            "json" => Some(MachineFormat::Json),
+            "toml" => Some(MachineFormat::Toml),
+            "jff" | "jflap" => Some(MachineFormat::Jflap),
+            _ => None,
+        }
+    }
+
+    // Formato deduzido da extensão do arquivo; sem uma extensão conhecida, o arquivo é
+    // tratado como um arquivo de descrição
+    pub fn from_path(path: &str) -> MachineFormat {
+        let extension = path.rsplit_once('.').map(|(_, extension)| extension.to_lowercase()).unwrap_or_default();
+        match extension.as_str() {
+            "json" => MachineFormat::Json,
+            "toml" => MachineFormat::Toml,
+            "jff" => MachineFormat::Jflap,
+            _ => MachineFormat::Description,
+        }
+    }
+}
+
+// Erro na leitura de um arquivo JSON ou TOML: de sintaxe, com a linha e a coluna, ou
+// de estrutura, com o caminho do campo, como transitions[2].move
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    Syntax { line: usize, column: usize, message: String },
+    Schema { path: String, message: String },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Syntax { line, column, message } => {
+                write!(f, "syntax error at line {}, column {}: {}", line, column, message)
+            }
+            FormatError::Schema { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+// Valor lido de um documento JSON ou TOML. As tabelas mantêm a ordem das chaves.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    // Nome do tipo do valor, usado nas mensagens de erro
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Integer(_) => "an integer",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+}
+
+// Posição em um texto, com a linha e a coluna atuais, usada pelos leitores de JSON e
+// de TOML
+pub(crate) struct Cursor {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Cursor {
+    pub(crate) fn new(source: &str) -> Cursor {
+        Cursor { chars: source.chars().collect(), position: 0, line: 1, column: 1 }
+    }
+
+    pub(crate) fn error(&self, message: impl Into<String>) -> FormatError {
+        FormatError::Syntax { line: self.line, column: self.column, message: message.into() }
+    }
+
+    // Linha e coluna atuais, para erros apontados depois de lido o trecho
+    pub(crate) fn location(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    pub(crate) fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, expected)| self.chars.get(self.position + i) == Some(&expected))
+    }
+
+    pub(crate) fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    // Avança sobre o texto, se ele vier a seguir
+    pub(crate) fn consume(&mut self, text: &str) -> bool {
+        if !self.starts_with(text) {
+            return false;
+        }
+        for _ in text.chars() {
+            self.advance();
+        }
+        true
+    }
+
+    pub(crate) fn expect(&mut self, expected: char) -> Result<(), FormatError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.advance();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}', found end of file", expected))),
+        }
+    }
+
+    // Lê um número inteiro com sinal; '_' separa dígitos quando `underscores` é verdadeiro
+    pub(crate) fn read_integer(&mut self, underscores: bool) -> Result<i64, FormatError> {
+        let mut text = String::new();
+        if let Some(sign @ ('-' | '+')) = self.peek() {
+            text.push(sign);
+            self.advance();
+        }
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() {
+                text.push(c);
+            } else if !(underscores && c == '_') {
+                break;
+            }
+            self.advance();
+        }
+        if matches!(self.peek(), Some('.' | 'e' | 'E')) {
+            return Err(self.error("only integer numbers are supported"));
+        }
+        text.parse().map_err(|_| self.error(format!("invalid number '{}'", text)))
+    }
+
+    // Lê as quatro casas hexadecimais de um escape \uXXXX
+    pub(crate) fn read_hex4(&mut self) -> Result<u32, FormatError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.peek().and_then(|c| c.to_digit(16)).ok_or_else(|| self.error("invalid unicode escape"))?;
+            code = code * 16 + digit;
+            self.advance();
+        }
+        Ok(code)
+    }
+}
+
+// Escapa uma string para as strings entre aspas duplas do JSON e do TOML
+pub(crate) fn escape_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Símbolos e movimentos de uma transição: um valor simples com uma fita e uma lista com
+// mais de uma
+fn per_tape<T>(items: &[T], value: impl Fn(&T) -> Value) -> Value {
+    match items {
+        [item] => value(item),
+        _ => Value::Array(items.iter().map(value).collect()),
+    }
+}
+
+// Conjunto da máquina, com cada elemento uma única vez: a descrição aceita elementos
+// repetidos, mas a leitura do JSON e do TOML não
+fn string_list(items: &[String]) -> Value {
+    let mut list: Vec<Value> = Vec::new();
+    for item in items {
+        let item = Value::String(item.clone());
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    }
+    Value::Array(list)
+}
+
+// Função para converter a máquina de Turing na estrutura comum ao JSON e ao TOML
+pub fn machine_to_value(tm: &TuringMachine) -> Value {
+    let transitions = tm
+        .transitions
+        .iter()
+        .map(|transition| {
+            Value::Table(vec![
+                ("from".to_string(), Value::String(transition.from_state.clone())),
+                ("read".to_string(), per_tape(&transition.read_symbols, |symbol| Value::String(symbol.clone()))),
+                ("to".to_string(), Value::String(transition.to_state.clone())),
+                ("write".to_string(), per_tape(&transition.write_symbols, |symbol| Value::String(symbol.clone()))),
+                ("move".to_string(), per_tape(&transition.moves, |direction| Value::String(direction.to_string()))),
+            ])
+        })
+        .collect();
+
+    Value::Table(vec![
+        ("states".to_string(), string_list(&tm.states)),
+        ("input_alphabet".to_string(), string_list(&tm.alphabet)),
+        ("tape_alphabet".to_string(), string_list(&tm.tape_alphabet)),
+        ("blank".to_string(), Value::String(tm.blank.clone())),
+        ("initial_state".to_string(), Value::String(tm.initial_state.clone())),
+        ("final_states".to_string(), string_list(&tm.accept_states)),
+        ("reject_states".to_string(), string_list(&tm.reject_states)),
+        ("tapes".to_string(), Value::Integer(tm.tapes as i64)),
+        ("transitions".to_string(), Value::Array(transitions)),
+    ])
+}
+
+// Campos de uma tabela, consumidos um a um para que os que sobrarem sejam apontados
+// como desconhecidos
+struct Fields<'a> {
+    path: String,
+    entries: Vec<(&'a str, &'a Value)>,
+}
+
+fn schema_error(path: &str, message: impl Into<String>) -> FormatError {
+    FormatError::Schema { path: path.to_string(), message: message.into() }
+}
+
+impl<'a> Fields<'a> {
+    fn new(value: &'a Value, path: &str) -> Result<Fields<'a>, FormatError> {
+        match value {
+            Value::Table(entries) => Ok(Fields {
+                path: path.to_string(),
+                entries: entries.iter().map(|(key, value)| (key.as_str(), value)).collect(),
+            }),
+            other => Err(schema_error(path, format!("expected a table, found {}", other.kind()))),
+        }
+    }
+
+    fn field_path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        }
+    }
+
+    fn take(&mut self, key: &str) -> Option<(&'a Value, String)> {
+        let index = self.entries.iter().position(|(name, _)| *name == key)?;
+        let (_, value) = self.entries.remove(index);
+        Some((value, self.field_path(key)))
+    }
+
+    fn required(&mut self, key: &str) -> Result<(&'a Value, String), FormatError> {
+        self.take(key).ok_or_else(|| schema_error(&self.path_or_root(), format!("missing field '{}'", key)))
+    }
+
+    fn path_or_root(&self) -> String {
+        if self.path.is_empty() {
+            "document".to_string()
+        } else {
+            self.path.clone()
+        }
+    }
+
+    fn finish(self) -> Result<(), FormatError> {
+        match self.entries.first() {
+            Some((key, _)) => Err(schema_error(&self.field_path(key), "unknown field")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn expect_string(value: &Value, path: &str) -> Result<String, FormatError> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        other => Err(schema_error(path, format!("expected a string, found {}", other.kind()))),
+    }
+}
+
+fn expect_symbol(value: &Value, path: &str) -> Result<String, FormatError> {
+    let text = expect_string(value, path)?;
+    if text.is_empty() {
+        return Err(schema_error(path, "expected a symbol, found an empty string"));
+    }
+    Ok(text)
+}
+
+fn expect_state(value: &Value, path: &str) -> Result<String, FormatError> {
+    let text = expect_string(value, path)?;
+    if text.is_empty() {
+        return Err(schema_error(path, "expected a state name, found an empty string"));
+    }
+    Ok(text)
+}
+
+fn expect_list<T>(
+    value: &Value,
+    path: &str,
+    item: impl Fn(&Value, &str) -> Result<T, FormatError>,
+) -> Result<Vec<T>, FormatError> {
+    match value {
+        Value::Array(items) => {
+            items.iter().enumerate().map(|(i, value)| item(value, &format!("{}[{}]", path, i))).collect()
+        }
+        other => Err(schema_error(path, format!("expected an array, found {}", other.kind()))),
+    }
+}
+
+// Lista sem elementos repetidos, como um conjunto de estados ou um alfabeto
+fn expect_set(
+    value: &Value,
+    path: &str,
+    item: impl Fn(&Value, &str) -> Result<String, FormatError>,
+) -> Result<Vec<String>, FormatError> {
+    let items = expect_list(value, path, item)?;
+    for (i, name) in items.iter().enumerate() {
+        if items[..i].contains(name) {
+            return Err(schema_error(&format!("{}[{}]", path, i), format!("'{}' appears more than once", name)));
+        }
+    }
+    Ok(items)
+}
+
+// Lista de valores por fita; um valor simples vale para uma única fita
+fn expect_per_tape<T>(
+    value: &Value,
+    path: &str,
+    item: impl Fn(&Value, &str) -> Result<T, FormatError>,
+) -> Result<Vec<T>, FormatError> {
+    match value {
+        Value::Array(_) => expect_list(value, path, item),
+        _ => Ok(vec![item(value, path)?]),
+    }
+}
+
+fn expect_move(value: &Value, path: &str) -> Result<Move, FormatError> {
+    let text = expect_string(value, path)?;
+    Move::from_symbol(&text).ok_or_else(|| schema_error(path, format!("invalid move '{}', expected L, R or S", text)))
+}
+
+// Função para converter a estrutura lida de um arquivo JSON ou TOML em uma máquina de
+// Turing, com as mesmas verificações do arquivo de descrição sobre o branco e o número
+// de fitas
+pub fn machine_from_value(value: &Value) -> Result<TuringMachine, FormatError> {
+    let mut fields = Fields::new(value, "")?;
+    let (states, path) = fields.required("states")?;
+    let states = expect_set(states, &path, expect_state)?;
+    let (alphabet, path) = fields.required("input_alphabet")?;
+    let alphabet = expect_set(alphabet, &path, expect_symbol)?;
+    let (tape_alphabet, path) = fields.required("tape_alphabet")?;
+    let tape_alphabet = expect_set(tape_alphabet, &path, expect_symbol)?;
+    let blank = match fields.take("blank") {
+        Some((blank, path)) => expect_symbol(blank, &path)?,
+        None => DEFAULT_BLANK.to_string(),
+    };
+    let (initial_state, path) = fields.required("initial_state")?;
+    let initial_state = expect_state(initial_state, &path)?;
+    let (accept_states, path) = fields.required("final_states")?;
+    let accept_states = expect_set(accept_states, &path, expect_state)?;
+    let reject_states = match fields.take("reject_states") {
+        Some((reject_states, path)) => expect_set(reject_states, &path, expect_state)?,
+        None => Vec::new(),
+    };
+    let tapes = match fields.take("tapes") {
+        Some((Value::Integer(tapes), _)) if (1..=MAX_TAPES as i64).contains(tapes) => Some(*tapes as usize),
+        Some((Value::Integer(_), path)) => {
+            return Err(schema_error(&path, format!("expected between 1 and {} tapes", MAX_TAPES)))
+        }
+        Some((other, path)) => return Err(schema_error(&path, format!("expected a positive integer, found {}", other.kind()))),
+        None => None,
+    };
+    let (transitions, path) = fields.required("transitions")?;
+    let transitions = expect_list(transitions, &path, |value, path| {
+        let mut fields = Fields::new(value, path)?;
+        let (from_state, path) = fields.required("from")?;
+        let from_state = expect_state(from_state, &path)?;
+        let (read_symbols, path) = fields.required("read")?;
+        let read_symbols = expect_per_tape(read_symbols, &path, expect_symbol)?;
+        let (to_state, path) = fields.required("to")?;
+        let to_state = expect_state(to_state, &path)?;
+        let (write_symbols, write_path) = fields.required("write")?;
+        let write_symbols = expect_per_tape(write_symbols, &write_path, expect_symbol)?;
+        let (moves, move_path) = fields.required("move")?;
+        let moves = expect_per_tape(moves, &move_path, expect_move)?;
+        fields.finish()?;
+
+        let tapes = read_symbols.len();
+        if write_symbols.len() != tapes {
+            return Err(schema_error(&write_path, format!("expected {} symbol(s), one per tape", tapes)));
+        }
+        if moves.len() != tapes {
+            return Err(schema_error(&move_path, format!("expected {} move(s), one per tape", tapes)));
+        }
+        Ok(Transition { from_state, read_symbols, to_state, write_symbols, moves })
+    })?;
+    fields.finish()?;
+
+    // Todas as transições devem ler o mesmo número de fitas
+    let tapes = tapes.or_else(|| transitions.first().map(Transition::tapes)).unwrap_or(1);
+    if let Some(i) = transitions.iter().position(|transition| transition.tapes() != tapes) {
+        let message = format!("transition reads {} tape(s), but the machine has {}", transitions[i].tapes(), tapes);
+        return Err(schema_error(&format!("transitions[{}].read", i), message));
+    }
+
+    if alphabet.contains(&blank) {
+        return Err(schema_error(
+            "input_alphabet",
+            format!("the blank symbol '{}' cannot be part of the input alphabet", blank),
+        ));
+    }
+    if !tape_alphabet.contains(&blank) {
+        return Err(schema_error(
+            "tape_alphabet",
+            format!("the blank symbol '{}' must be part of the tape alphabet", blank),
+        ));
+    }
+
+    Ok(TuringMachine {
+        states,
+        alphabet,
+        tape_alphabet,
+        transitions,
+        initial_state,
+        accept_states,
+        reject_states,
+        blank,
+        tapes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_json, to_json};
+    use crate::parse_description;
+    use crate::toml::{parse_toml, to_toml};
+
+    // Máquina com nomes que precisam de aspas, símbolos de vários caracteres e um grafema
+    // com acento combinante
+    const QUOTED: &str = "Q = {q0, \"q fim\", \"q,1\"}\nSigma = {a, X1, \"e\u{301}\"}\n\
+                          Gamma = {a, X1, \"e\u{301}\", \"\\\"\", \"\\\\\", \"*\", \",\", \"fim de bloco\", B}\n\
+                          q0 = q0\nF = {\"q fim\"}\nR = {\"q,1\"}\ndelta:\n\
+                          (q0, \"*\") -> (\"q,1\", \"fim de bloco\", R)\n(q0, X1) -> (q0, \"\\\"\", L)\n\
+                          (q0, \"e\u{301}\") -> (\"q fim\", \"\\\\\", S)\n(q0, \",\") -> (q0, \",\", R)\n";
+
+    // Máquina de duas fitas
+    const TWO_TAPES: &str = "Q = {q0, q1}\nSigma = {a}\nGamma = {a, #a, B}\nq0 = q0\nF = {q1}\ndelta:\n\
+                             (q0, a, B) -> (q0, a, #a, R, R)\n(q0, B, B) -> (q1, B, B, S, L)\n";
+
+    // Máquina de três fitas sem transições, cujo número de fitas só aparece na seção tapes
+    const THREE_TAPES: &str = "Q = {q0}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {q0}\ntapes = 3\ndelta:\n";
+
+    fn machine(source: &str) -> TuringMachine {
+        parse_description(source, "test.txt").unwrap()
+    }
+
+    // Lê a máquina de um JSON, que deve ser rejeitado com o erro no campo indicado
+    fn schema_error_path(source: &str) -> String {
+        match parse_json(source) {
+            Err(FormatError::Schema { path, .. }) => path,
+            other => panic!("expected a schema error, found {:?}", other),
+        }
+    }
+
+    const MINIMAL: &str = r#""input_alphabet": ["a"], "tape_alphabet": ["a", "B"], "initial_state": "q0", "final_states": []"#;
+
+    #[test]
+    fn json_round_trip_keeps_the_machine() {
+        for source in [QUOTED, TWO_TAPES, THREE_TAPES] {
+            let tm = machine(source);
+            assert_eq!(parse_json(&to_json(&tm)).unwrap(), tm);
+        }
+    }
+
+    #[test]
+    fn toml_round_trip_keeps_the_machine() {
+        for source in [QUOTED, TWO_TAPES, THREE_TAPES] {
+            let tm = machine(source);
+            assert_eq!(parse_toml(&to_toml(&tm)).unwrap(), tm);
+        }
+    }
+
+    #[test]
+    fn description_round_trip_keeps_the_machine() {
+        for source in [QUOTED, TWO_TAPES, THREE_TAPES] {
+            let tm = machine(source);
+            assert_eq!(machine(&tm.to_string()), tm);
+            assert_eq!(machine(&parse_json(&to_json(&tm)).unwrap().to_string()), tm);
+        }
+    }
+
+    #[test]
+    fn repeated_entries_of_a_description_are_written_once() {
+        let tm = machine("Q = {q0, q1, q0}\nSigma = {a, a}\nGamma = {a, B, a}\nq0 = q0\nF = {q1, q1}\ndelta:\n\
+                          (q0, a) -> (q1, a, R)\n");
+        let mut expected = tm.clone();
+        expected.states = vec!["q0".to_string(), "q1".to_string()];
+        expected.alphabet = vec!["a".to_string()];
+        expected.tape_alphabet = vec!["a".to_string(), "B".to_string()];
+        expected.accept_states = vec!["q1".to_string()];
+        assert_eq!(parse_json(&to_json(&tm)).unwrap(), expected);
+        assert_eq!(parse_toml(&to_toml(&tm)).unwrap(), expected);
+        assert_eq!(machine(&parse_json(&to_json(&tm)).unwrap().to_string()), expected);
+    }
+
+    #[test]
+    fn empty_names_are_rejected() {
+        let source = format!(r#"{{"states": ["q0", ""], {}, "transitions": []}}"#, MINIMAL);
+        assert_eq!(schema_error_path(&source), "states[1]");
+        let source = format!(r#"{{"states": ["q0"], {}, "transitions": [
+            {{"from": "q0", "read": "a", "to": "", "write": "a", "move": "R"}}]}}"#, MINIMAL);
+        assert_eq!(schema_error_path(&source), "transitions[0].to");
+        let source = r#"{"states": ["q0"], "input_alphabet": [""], "tape_alphabet": ["B"], "initial_state": "q0",
+            "final_states": [], "transitions": []}"#;
+        assert_eq!(schema_error_path(source), "input_alphabet[0]");
+    }
+
+    #[test]
+    fn repeated_entries_are_rejected() {
+        let source = format!(r#"{{"states": ["q0", "q1", "q0"], {}, "transitions": []}}"#, MINIMAL);
+        assert_eq!(schema_error_path(&source), "states[2]");
+        let source = r#"{"states": ["q0"], "input_alphabet": ["a"], "tape_alphabet": ["a", "B", "a"],
+            "initial_state": "q0", "final_states": [], "transitions": []}"#;
+        assert_eq!(schema_error_path(source), "tape_alphabet[2]");
+    }
+
+    #[test]
+    fn tape_count_is_limited() {
+        for tapes in [0, MAX_TAPES as i64 + 1, 1 << 40] {
+            let source = format!(r#"{{"states": ["q0"], {}, "tapes": {}, "transitions": []}}"#, MINIMAL, tapes);
+            assert_eq!(schema_error_path(&source), "tapes");
+        }
+        let source = format!(r#"{{"states": ["q0"], {}, "tapes": {}, "transitions": []}}"#, MINIMAL, MAX_TAPES);
+        assert_eq!(parse_json(&source).unwrap().tapes, MAX_TAPES);
+    }
+}
@@ -0,0 +1,676 @@
+// Leitura e escrita de máquinas de Turing no formato XML (.jff) do JFLAP, para que as
+// máquinas possam ser trocadas entre o JFLAP e este simulador. Apenas máquinas de uma
+// fita são aceitas. Um arquivo do JFLAP tem a forma:
+//
+//     <structure>
+//         <type>turing</type>
+//         <automaton>
+//             <state id="0" name="q0"><x>100.0</x><y>100.0</y><initial/></state>
+//             <state id="1" name="q1"><x>250.0</x><y>100.0</y><final/></state>
+//             <transition>
+//                 <from>0</from><to>1</to>
+//                 <read>a</read><write/><move>R</move>
+//             </transition>
+//         </automaton>
+//     </structure>
+//
+// No JFLAP, o símbolo branco é representado por um elemento <read/> ou <write/> vazio,
+// e os movimentos são L, R e S, os mesmos da descrição. O JFLAP não guarda os
+// alfabetos nem os estados de rejeição: na leitura, Γ reúne os símbolos das transições
+// e Σ os símbolos lidos que nenhuma transição escreve no lugar de outro símbolo; na
+// escrita, os estados de rejeição viram estados comuns, sem transições de saída.
+//
+// No JFLAP, a cabeça começa sobre o primeiro símbolo da palavra, e não sobre o branco à
+// sua esquerda. Por isso, a leitura acrescenta um novo estado inicial, chamado start,
+// que apenas move a cabeça para a direita, e a escrita um que a move para a esquerda.
+// Um estado desses, quando já existe, é removido em vez de duplicado, de modo que a
+// conversão de ida e volta devolve a máquina original.
+
+use std::fmt;
+use std::fmt::Write;
+
+use crate::diagram::bfs_layers;
+use crate::symbol::is_single_grapheme;
+use crate::{Move, Transition, TuringMachine};
+
+// Símbolos usados como branco na leitura, na ordem de preferência; o escolhido é o
+// primeiro que não aparece nas transições
+const BLANK_CANDIDATES: [&str; 3] = ["B", "□", "_"];
+
+// Nome do estado inicial acrescentado para ajustar a posição inicial da cabeça
+const START_STATE: &str = "start";
+
+// Tipos de erro na leitura ou na escrita de um arquivo do JFLAP
+#[derive(Debug, Clone, PartialEq)]
+pub enum JflapError {
+    Xml { line: usize, column: usize, message: String },
+    NotTuringMachine { found: String },
+    MultiTape { tapes: usize },
+    MissingElement { element: &'static str, parent: &'static str },
+    DuplicateStateId { id: String },
+    UnknownStateId { id: String },
+    MissingInitialState,
+    InvalidSymbol { element: &'static str, found: String },
+    InvalidMove { found: String },
+    MultiCharacterSymbol { symbol: String },
+}
+
+impl fmt::Display for JflapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JflapError::Xml { line, column, message } => {
+                write!(f, "invalid XML at line {}, column {}: {}", line, column, message)
+            }
+            JflapError::NotTuringMachine { found } => {
+                write!(f, "expected a JFLAP Turing machine, found a structure of type '{}'", found)
+            }
+            JflapError::MultiTape { tapes } => {
+                write!(f, "only single-tape machines are supported, but this machine has {} tapes", tapes)
+            }
+            JflapError::MissingElement { element, parent } => write!(f, "missing <{}> in <{}>", element, parent),
+            JflapError::DuplicateStateId { id } => write!(f, "state id '{}' is used more than once", id),
+            JflapError::UnknownStateId { id } => write!(f, "transition refers to unknown state id '{}'", id),
+            JflapError::MissingInitialState => write!(f, "the machine has no initial state"),
+            JflapError::InvalidSymbol { element, found } => {
+                write!(f, "<{}> must contain a single symbol, found '{}'", element, found)
+            }
+            JflapError::InvalidMove { found } => write!(f, "invalid move '{}', expected L, R or S", found),
+            JflapError::MultiCharacterSymbol { symbol } => {
+                write!(f, "JFLAP only supports single-character symbols, found '{}'", symbol)
+            }
+        }
+    }
+}
+
+// Elemento XML com os atributos, os elementos filhos e o texto contido diretamente nele
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+// Leitor de XML mínimo, suficiente para os arquivos do JFLAP: elementos, atributos,
+// texto, referências a entidades, comentários, seções CDATA e declarações, que são
+// ignoradas
+struct XmlReader {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    column: usize,
+}
+
+impl XmlReader {
+    fn new(source: &str) -> XmlReader {
+        XmlReader { chars: source.chars().collect(), position: 0, line: 1, column: 1 }
+    }
+
+    fn error(&self, message: impl Into<String>) -> JflapError {
+        JflapError::Xml { line: self.line, column: self.column, message: message.into() }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, expected)| self.chars.get(self.position + i) == Some(&expected))
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), JflapError> {
+        if !self.starts_with(text) {
+            return Err(self.error(format!("expected '{}'", text)));
+        }
+        for _ in text.chars() {
+            self.advance();
+        }
+        Ok(())
+    }
+
+    // Avança até depois do texto dado, que encerra um comentário ou uma declaração
+    fn skip_past(&mut self, end: &str) -> Result<(), JflapError> {
+        while !self.starts_with(end) {
+            if self.advance().is_none() {
+                return Err(self.error(format!("unterminated markup, expected '{}'", end)));
+            }
+        }
+        self.expect(end)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.advance();
+        }
+    }
+
+    // Ignora espaços, comentários e declarações entre os elementos
+    fn skip_misc(&mut self) -> Result<(), JflapError> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.starts_with("<!") && !self.starts_with("<![CDATA[") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_name(&mut self) -> Result<String, JflapError> {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<') {
+                break;
+            }
+            name.push(c);
+            self.advance();
+        }
+        if name.is_empty() {
+            return Err(self.error("expected a name"));
+        }
+        Ok(name)
+    }
+
+    // Lê uma referência a entidade, como &amp; ou &#65;, já depois do '&'
+    fn read_entity(&mut self) -> Result<char, JflapError> {
+        let mut name = String::new();
+        loop {
+            match self.advance() {
+                Some(';') => break,
+                Some(c) if name.len() < 10 => name.push(c),
+                _ => return Err(self.error("unterminated entity reference")),
+            }
+        }
+        let code = if let Some(hex) = name.strip_prefix("#x") {
+            u32::from_str_radix(hex, 16).ok()
+        } else if let Some(decimal) = name.strip_prefix('#') {
+            decimal.parse().ok()
+        } else {
+            None
+        };
+        match name.as_str() {
+            "lt" => Ok('<'),
+            "gt" => Ok('>'),
+            "amp" => Ok('&'),
+            "quot" => Ok('"'),
+            "apos" => Ok('\''),
+            _ => code.and_then(char::from_u32).ok_or_else(|| self.error(format!("unknown entity '&{};'", name))),
+        }
+    }
+
+    fn read_attribute_value(&mut self) -> Result<String, JflapError> {
+        let quote = match self.advance() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error("expected a quoted attribute value")),
+        };
+        let mut value = String::new();
+        loop {
+            match self.advance() {
+                Some(c) if c == quote => return Ok(value),
+                Some('&') => value.push(self.read_entity()?),
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated attribute value")),
+            }
+        }
+    }
+
+    // Lê um elemento completo, a partir do '<' de abertura
+    fn read_element(&mut self) -> Result<Element, JflapError> {
+        self.expect("<")?;
+        let name = self.read_name()?;
+        let mut element = Element { name, attributes: Vec::new(), children: Vec::new(), text: String::new() };
+
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.expect("/>")?;
+                return Ok(element);
+            }
+            if self.starts_with(">") {
+                self.expect(">")?;
+                break;
+            }
+            let attribute = self.read_name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let value = self.read_attribute_value()?;
+            element.attributes.push((attribute, value));
+        }
+
+        loop {
+            if self.starts_with("</") {
+                self.expect("</")?;
+                let closing = self.read_name()?;
+                if closing != element.name {
+                    return Err(self.error(format!("expected </{}>, found </{}>", element.name, closing)));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if self.starts_with("<![CDATA[") {
+                self.expect("<![CDATA[")?;
+                while !self.starts_with("]]>") {
+                    let c = self.advance().ok_or_else(|| self.error("unterminated CDATA section"))?;
+                    element.text.push(c);
+                }
+                self.expect("]]>")?;
+            } else if self.starts_with("<!--") || self.starts_with("<?") {
+                self.skip_misc()?;
+            } else if self.starts_with("<") {
+                element.children.push(self.read_element()?);
+            } else {
+                match self.advance() {
+                    Some('&') => {
+                        let c = self.read_entity()?;
+                        element.text.push(c);
+                    }
+                    Some(c) => element.text.push(c),
+                    None => return Err(self.error(format!("unexpected end of file inside <{}>", element.name))),
+                }
+            }
+        }
+    }
+
+    // Lê o documento, que deve ter um único elemento raiz
+    fn read_document(mut self) -> Result<Element, JflapError> {
+        self.skip_misc()?;
+        if self.peek().is_none() {
+            return Err(self.error("empty document"));
+        }
+        let root = self.read_element()?;
+        self.skip_misc()?;
+        if self.peek().is_some() {
+            return Err(self.error("unexpected content after the root element"));
+        }
+        Ok(root)
+    }
+}
+
+// Símbolo de <read> ou <write>: vazio representa o branco
+fn read_symbol(element: &Element, name: &'static str) -> Result<Option<String>, JflapError> {
+    if element.text.is_empty() {
+        Ok(None)
+    } else if is_single_grapheme(&element.text) {
+        Ok(Some(element.text.clone()))
+    } else {
+        Err(JflapError::InvalidSymbol { element: name, found: element.text.clone() })
+    }
+}
+
+// Transição lida do arquivo, com o branco ainda representado por None
+struct JflapTransition {
+    from_state: String,
+    to_state: String,
+    read: Option<String>,
+    write: Option<String>,
+    direction: Move,
+}
+
+// Função para ler uma máquina de Turing de uma fita a partir do conteúdo de um arquivo
+// .jff do JFLAP
+pub fn parse_jff(source: &str) -> Result<TuringMachine, JflapError> {
+    let root = XmlReader::new(source).read_document()?;
+    if root.name != "structure" {
+        return Err(JflapError::MissingElement { element: "structure", parent: "document" });
+    }
+    let kind = root.child("type").map_or("", |kind| kind.text.trim());
+    if kind != "turing" {
+        return Err(JflapError::NotTuringMachine { found: kind.to_string() });
+    }
+    if let Some(tapes) = root.child("tapes") {
+        let tapes = tapes.text.trim().parse().unwrap_or(0);
+        if tapes != 1 {
+            return Err(JflapError::MultiTape { tapes });
+        }
+    }
+    let automaton =
+        root.child("automaton").ok_or(JflapError::MissingElement { element: "automaton", parent: "structure" })?;
+
+    // Estados: o JFLAP identifica os estados pelo atributo id e exibe o atributo name
+    let mut ids: Vec<(String, String)> = Vec::new();
+    let mut initial_state = None;
+    let mut accept_states = Vec::new();
+    for state in automaton.children_named("state") {
+        let id = state.attribute("id").unwrap_or_default().to_string();
+        if ids.iter().any(|(other, _)| *other == id) {
+            return Err(JflapError::DuplicateStateId { id });
+        }
+        let name = state.attribute("name").map_or_else(|| format!("q{}", id), str::to_string);
+        if state.child("initial").is_some() {
+            initial_state = Some(name.clone());
+        }
+        if state.child("final").is_some() {
+            accept_states.push(name.clone());
+        }
+        ids.push((id, name));
+    }
+    let state_name = |id: &Element| {
+        let id = id.text.trim();
+        ids.iter()
+            .find(|(other, _)| other == id)
+            .map(|(_, name)| name.clone())
+            .ok_or_else(|| JflapError::UnknownStateId { id: id.to_string() })
+    };
+
+    let mut transitions = Vec::new();
+    for transition in automaton.children_named("transition") {
+        let child = |element: &'static str| {
+            transition.child(element).ok_or(JflapError::MissingElement { element, parent: "transition" })
+        };
+        if transition.children_named("read").any(|read| read.attribute("tape").is_some_and(|tape| tape != "1")) {
+            let tapes = transition.children_named("read").count();
+            return Err(JflapError::MultiTape { tapes });
+        }
+        let direction = child("move")?.text.trim();
+        transitions.push(JflapTransition {
+            from_state: state_name(child("from")?)?,
+            to_state: state_name(child("to")?)?,
+            read: read_symbol(child("read")?, "read")?,
+            write: read_symbol(child("write")?, "write")?,
+            direction: Move::from_symbol(direction).ok_or_else(|| JflapError::InvalidMove { found: direction.to_string() })?,
+        });
+    }
+
+    let used: Vec<&String> =
+        transitions.iter().flat_map(|transition| [&transition.read, &transition.write]).flatten().collect();
+    let blank =
+        BLANK_CANDIDATES.into_iter().find(|candidate| !used.iter().any(|symbol| symbol == candidate)).unwrap_or("B");
+    let blank = blank.to_string();
+
+    // Γ reúne os símbolos na ordem em que aparecem; Σ, os lidos que nunca são escritos
+    // sobre um símbolo diferente, como os X e Y que marcam a palavra já processada
+    let mut tape_alphabet: Vec<String> = Vec::new();
+    for &symbol in &used {
+        if !tape_alphabet.contains(symbol) {
+            tape_alphabet.push(symbol.clone());
+        }
+    }
+    let introduced = |symbol: &String| {
+        transitions.iter().any(|transition| {
+            transition.write.as_ref() == Some(symbol) && transition.read.as_ref() != Some(symbol)
+        })
+    };
+    let read = |symbol: &String| transitions.iter().any(|transition| transition.read.as_ref() == Some(symbol));
+    let alphabet = tape_alphabet.iter().filter(|symbol| read(symbol) && !introduced(symbol)).cloned().collect();
+    tape_alphabet.push(blank.clone());
+
+    let mut tm = TuringMachine {
+        states: ids.into_iter().map(|(_, name)| name).collect(),
+        alphabet,
+        tape_alphabet,
+        transitions: transitions
+            .into_iter()
+            .map(|transition| Transition {
+                from_state: transition.from_state,
+                read_symbols: vec![transition.read.unwrap_or_else(|| blank.clone())],
+                to_state: transition.to_state,
+                write_symbols: vec![transition.write.unwrap_or_else(|| blank.clone())],
+                moves: vec![transition.direction],
+            })
+            .collect(),
+        initial_state: initial_state.ok_or(JflapError::MissingInitialState)?,
+        accept_states,
+        reject_states: Vec::new(),
+        blank,
+        tapes: 1,
+    };
+    adjust_head_start(&mut tm, Move::Right);
+    Ok(tm)
+}
+
+// Ajusta a posição inicial da cabeça com um novo estado inicial que a move uma célula na
+// direção dada sem alterar a fita. Se o estado inicial já for um estado assim, criado
+// pela conversão no sentido oposto, ele é removido.
+fn adjust_head_start(tm: &mut TuringMachine, direction: Move) {
+    let opposite = if direction == Move::Right { Move::Left } else { Move::Right };
+    if let Some(next_state) = head_start_target(tm, opposite) {
+        let start = std::mem::replace(&mut tm.initial_state, next_state);
+        tm.states.retain(|state| *state != start);
+        tm.transitions.retain(|transition| transition.from_state != start);
+        return;
+    }
+
+    let mut start = START_STATE.to_string();
+    let mut suffix = 1;
+    let used = |name: &str, tm: &TuringMachine| {
+        tm.states.iter().chain(&tm.accept_states).any(|state| state == name)
+            || tm.transitions.iter().any(|transition| transition.from_state == name || transition.to_state == name)
+    };
+    while used(&start, tm) {
+        start = format!("{}{}", START_STATE, suffix);
+        suffix += 1;
+    }
+
+    // Ao mover para a direita, a cabeça está sempre sobre o branco; ao mover para a
+    // esquerda, pode estar sobre qualquer símbolo
+    let symbols: Vec<String> = if direction == Move::Right {
+        vec![tm.blank.clone()]
+    } else {
+        tm.tape_alphabet.clone()
+    };
+    let adapter = symbols.into_iter().map(|symbol| Transition {
+        from_state: start.clone(),
+        read_symbols: vec![symbol.clone()],
+        to_state: tm.initial_state.clone(),
+        write_symbols: vec![symbol],
+        moves: vec![direction],
+    });
+    tm.transitions.splice(0..0, adapter);
+    tm.states.insert(0, start.clone());
+    tm.initial_state = start;
+}
+
+// Se o estado inicial foi acrescentado por uma conversão anterior, ou seja, se tem o
+// nome start (seguido ou não de um número), apenas move a cabeça na direção dada sem
+// alterar a fita, sempre para o mesmo estado, e nenhuma transição volta a ele, devolve
+// esse próximo estado
+fn head_start_target(tm: &TuringMachine, direction: Move) -> Option<String> {
+    let start = &tm.initial_state;
+    let suffix = start.strip_prefix(START_STATE)?;
+    if !suffix.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if tm.accept_states.contains(start) || tm.transitions.iter().any(|transition| transition.to_state == *start) {
+        return None;
+    }
+    let mut transitions = tm.transitions.iter().filter(|transition| transition.from_state == *start).peekable();
+    let next_state = transitions.peek()?.to_state.clone();
+    let mut read = Vec::new();
+    for transition in transitions {
+        if transition.to_state != next_state
+            || transition.read_symbols != transition.write_symbols
+            || transition.moves != [direction]
+        {
+            return None;
+        }
+        read.extend(&transition.read_symbols);
+    }
+    // Movendo para a direita a partir do branco inicial, basta a transição que lê o
+    // branco; para a esquerda, todos os símbolos de Γ precisam ser tratados
+    let complete = match direction {
+        Move::Right => read.contains(&&tm.blank),
+        _ => tm.tape_alphabet.iter().all(|symbol| read.contains(&symbol)),
+    };
+    complete.then_some(next_state)
+}
+
+// Escapa os caracteres especiais do XML
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Elemento <read> ou <write>, vazio quando o símbolo é o branco
+fn symbol_element(name: &str, symbol: &str, blank: &str) -> String {
+    if symbol == blank {
+        format!("<{}/>", name)
+    } else {
+        format!("<{}>{}</{}>", name, escape_xml(symbol), name)
+    }
+}
+
+// Função para exportar uma máquina de Turing de uma fita no formato .jff do JFLAP. Os
+// estados são posicionados em colunas pela distância a partir do estado inicial.
+pub fn to_jff(tm: &TuringMachine) -> Result<String, JflapError> {
+    if tm.tapes != 1 {
+        return Err(JflapError::MultiTape { tapes: tm.tapes });
+    }
+    let symbols = tm.transitions.iter().flat_map(|transition| transition.read_symbols.iter().chain(&transition.write_symbols));
+    if let Some(symbol) = tm.tape_alphabet.iter().chain(symbols).find(|symbol| !is_single_grapheme(symbol)) {
+        return Err(JflapError::MultiCharacterSymbol { symbol: symbol.clone() });
+    }
+    let mut tm = tm.clone();
+    let reject_states = std::mem::take(&mut tm.reject_states);
+    tm.transitions.retain(|transition| !reject_states.contains(&transition.from_state));
+    adjust_head_start(&mut tm, Move::Left);
+    let tm = &tm;
+
+    let mut output = String::new();
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    output.push_str("<structure>\n");
+    output.push_str("\t<type>turing</type>\n");
+    output.push_str("\t<automaton>\n");
+
+    // Os estados são escritos na ordem de Q, cada um na posição da sua camada
+    let mut positions: Vec<(&str, usize, usize)> = Vec::new();
+    for (column, layer) in bfs_layers(tm).iter().enumerate() {
+        for (row, &state) in layer.iter().enumerate() {
+            positions.push((state, column, row));
+        }
+    }
+    positions.sort_by_key(|&(state, _, _)| tm.states.iter().position(|name| name == state).unwrap_or(usize::MAX));
+
+    let mut ids: Vec<&str> = Vec::new();
+    for (state, column, row) in positions {
+        writeln!(output, "\t\t<state id=\"{}\" name=\"{}\">", ids.len(), escape_xml(state)).unwrap();
+        writeln!(output, "\t\t\t<x>{:.1}</x>", 100.0 + 150.0 * column as f64).unwrap();
+        writeln!(output, "\t\t\t<y>{:.1}</y>", 100.0 + 120.0 * row as f64).unwrap();
+        if state == tm.initial_state {
+            output.push_str("\t\t\t<initial/>\n");
+        }
+        if tm.accept_states.iter().any(|accept| accept == state) {
+            output.push_str("\t\t\t<final/>\n");
+        }
+        output.push_str("\t\t</state>\n");
+        ids.push(state);
+    }
+
+    let id = |state: &str| ids.iter().position(|&name| name == state).unwrap();
+    for transition in &tm.transitions {
+        output.push_str("\t\t<transition>\n");
+        writeln!(output, "\t\t\t<from>{}</from>", id(&transition.from_state)).unwrap();
+        writeln!(output, "\t\t\t<to>{}</to>", id(&transition.to_state)).unwrap();
+        writeln!(output, "\t\t\t{}", symbol_element("read", &transition.read_symbols[0], &tm.blank)).unwrap();
+        writeln!(output, "\t\t\t{}", symbol_element("write", &transition.write_symbols[0], &tm.blank)).unwrap();
+        writeln!(output, "\t\t\t<move>{}</move>", transition.moves[0]).unwrap();
+        output.push_str("\t\t</transition>\n");
+    }
+
+    output.push_str("\t</automaton>\n");
+    output.push_str("</structure>\n");
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+
+    fn machine(source: &str) -> TuringMachine {
+        parse_description(source, "test.txt").unwrap()
+    }
+
+    // Máquina que troca a por X até o primeiro branco, com um símbolo acentuado e um que
+    // precisa de escape no XML
+    const MARKER: &str = "Q = {q0, q1}\nSigma = {a, \"é\", <}\nGamma = {a, \"é\", <, X, B}\nq0 = q0\nF = {q1}\n\
+                          delta:\n(q0, a) -> (q0, X, R)\n(q0, \"é\") -> (q0, \"é\", R)\n(q0, <) -> (q0, <, R)\n\
+                          (q0, X) -> (q0, X, R)\n(q0, B) -> (q1, B, L)\n";
+
+    #[test]
+    fn round_trip_keeps_the_machine() {
+        let tm = machine(MARKER);
+        let jff = to_jff(&tm).unwrap();
+        assert!(jff.contains("<read>&lt;</read>"));
+        assert_eq!(parse_jff(&jff).unwrap(), tm);
+    }
+
+    #[test]
+    fn head_starts_on_the_first_symbol_in_jflap() {
+        let tm = machine("Q = {q0, q1}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {q1}\ndelta:\n\
+                          (q0, a) -> (q1, a, S)\n");
+        let jff = to_jff(&tm).unwrap();
+        assert!(jff.contains("name=\"start\""));
+        let read = parse_jff(&jff).unwrap();
+        assert_eq!(read.initial_state, "q0");
+        assert!(!read.states.iter().any(|state| state == "start"));
+    }
+
+    #[test]
+    fn multi_tape_machines_are_refused() {
+        let tm = machine("Q = {q0}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {}\ndelta:\n\
+                          (q0, a, B) -> (q0, a, a, R, S)\n");
+        assert_eq!(to_jff(&tm), Err(JflapError::MultiTape { tapes: 2 }));
+
+        let jff = "<structure><type>turing</type><tapes>3</tapes><automaton/></structure>";
+        assert_eq!(parse_jff(jff), Err(JflapError::MultiTape { tapes: 3 }));
+        let jff = "<structure><type>turing</type><automaton>\
+                   <state id=\"0\" name=\"q0\"><initial/></state>\
+                   <transition><from>0</from><to>0</to><read tape=\"1\">a</read><read tape=\"2\"/>\
+                   <write tape=\"1\">a</write><write tape=\"2\"/><move tape=\"1\">R</move><move tape=\"2\">R</move>\
+                   </transition></automaton></structure>";
+        assert_eq!(parse_jff(jff), Err(JflapError::MultiTape { tapes: 2 }));
+    }
+
+    #[test]
+    fn multi_character_symbols_are_refused() {
+        let tm = machine("Q = {q0}\nSigma = {ab}\nGamma = {ab, B}\nq0 = q0\nF = {}\ndelta:\n(q0, ab) -> (q0, ab, R)\n");
+        assert_eq!(to_jff(&tm), Err(JflapError::MultiCharacterSymbol { symbol: "ab".to_string() }));
+    }
+
+    #[test]
+    fn other_automata_are_refused() {
+        let jff = "<structure><type>fa</type><automaton/></structure>";
+        assert_eq!(parse_jff(jff), Err(JflapError::NotTuringMachine { found: "fa".to_string() }));
+    }
+}
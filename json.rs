@@ -0,0 +1,230 @@
+// Leitura e escrita de máquinas de Turing em JSON, com a estrutura descrita em
+// format.rs. A escrita coloca cada transição em uma linha:
+//
+//     {
+//       "states": ["q0", "q1"],
+//       ...
+//       "transitions": [
+//         {"from": "q0", "read": "a", "to": "q1", "write": "a", "move": "R"}
+//       ]
+//     }
+
+use crate::format::{escape_string, machine_from_value, machine_to_value, Cursor, FormatError, Value};
+use crate::TuringMachine;
+
+// Leitor de JSON. Os números devem ser inteiros, já que a descrição de uma máquina não
+// tem outros números.
+struct JsonReader {
+    cursor: Cursor,
+}
+
+impl JsonReader {
+    fn skip_whitespace(&mut self) {
+        while self.cursor.peek().is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r')) {
+            self.cursor.advance();
+        }
+    }
+
+    fn read_keyword(&mut self, keyword: &str, value: Value) -> Result<Value, FormatError> {
+        if !self.cursor.consume(keyword) {
+            return Err(self.cursor.error("expected a value"));
+        }
+        Ok(value)
+    }
+
+    fn read_string(&mut self) -> Result<String, FormatError> {
+        self.cursor.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.cursor.advance() {
+                Some('"') => return Ok(text),
+                Some('\\') => {
+                    let escaped = match self.cursor.advance() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.read_unicode_escape()?,
+                        _ => return Err(self.cursor.error("invalid escape sequence")),
+                    };
+                    text.push(escaped);
+                }
+                Some(c) if c.is_control() => return Err(self.cursor.error("control character in string")),
+                Some(c) => text.push(c),
+                None => return Err(self.cursor.error("unterminated string")),
+            }
+        }
+    }
+
+    // Escape \uXXXX, já depois do 'u'; caracteres fora do plano básico vêm em pares de
+    // substitutos, como \ud83d\ude00
+    fn read_unicode_escape(&mut self) -> Result<char, FormatError> {
+        let high = self.cursor.read_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.cursor.consume("\\u") {
+                return Err(self.cursor.error("unpaired surrogate in unicode escape"));
+            }
+            let low = self.cursor.read_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.cursor.error("invalid surrogate pair in unicode escape"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.cursor.error("invalid unicode escape"))
+    }
+
+    fn read_value(&mut self) -> Result<Value, FormatError> {
+        self.skip_whitespace();
+        match self.cursor.peek() {
+            Some('{') => self.read_object(),
+            Some('[') => self.read_array(),
+            Some('"') => Ok(Value::String(self.read_string()?)),
+            Some('t') => self.read_keyword("true", Value::Bool(true)),
+            Some('f') => self.read_keyword("false", Value::Bool(false)),
+            Some('n') => self.read_keyword("null", Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Value::Integer(self.cursor.read_integer(false)?)),
+            Some(c) => Err(self.cursor.error(format!("expected a value, found '{}'", c))),
+            None => Err(self.cursor.error("expected a value, found end of file")),
+        }
+    }
+
+    fn read_array(&mut self) -> Result<Value, FormatError> {
+        self.cursor.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.cursor.peek() == Some(']') {
+            self.cursor.advance();
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.read_value()?);
+            self.skip_whitespace();
+            match self.cursor.advance() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.cursor.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn read_object(&mut self) -> Result<Value, FormatError> {
+        self.cursor.expect('{')?;
+        let mut entries: Vec<(String, Value)> = Vec::new();
+        self.skip_whitespace();
+        if self.cursor.peek() == Some('}') {
+            self.cursor.advance();
+            return Ok(Value::Table(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let (line, column) = self.cursor.location();
+            let key = self.read_string()?;
+            if entries.iter().any(|(other, _)| *other == key) {
+                return Err(FormatError::Syntax { line, column, message: format!("duplicate key '{}'", key) });
+            }
+            self.skip_whitespace();
+            self.cursor.expect(':')?;
+            let value = self.read_value()?;
+            entries.push((key, value));
+            self.skip_whitespace();
+            match self.cursor.advance() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Table(entries)),
+                _ => return Err(self.cursor.error("expected ',' or '}' in object")),
+            }
+        }
+    }
+}
+
+// Função para ler um documento JSON
+pub fn parse_value(source: &str) -> Result<Value, FormatError> {
+    let mut reader = JsonReader { cursor: Cursor::new(source) };
+    let value = reader.read_value()?;
+    reader.skip_whitespace();
+    if reader.cursor.peek().is_some() {
+        return Err(reader.cursor.error("unexpected content after the JSON value"));
+    }
+    Ok(value)
+}
+
+// Função para ler uma máquina de Turing de um documento JSON
+pub fn parse_json(source: &str) -> Result<TuringMachine, FormatError> {
+    machine_from_value(&parse_value(source)?)
+}
+
+// Escreve o valor em uma única linha
+fn write_inline(output: &mut String, value: &Value) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(value) => output.push_str(&value.to_string()),
+        Value::Integer(value) => output.push_str(&value.to_string()),
+        Value::String(text) => output.push_str(&escape_string(text)),
+        Value::Array(items) => {
+            output.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_inline(output, item);
+            }
+            output.push(']');
+        }
+        Value::Table(entries) => {
+            output.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                output.push_str(&escape_string(key));
+                output.push_str(": ");
+                write_inline(output, value);
+            }
+            output.push('}');
+        }
+    }
+}
+
+// Escreve o valor com uma entrada por linha quando ele é uma tabela ou uma lista de
+// tabelas, e em uma única linha nos demais casos
+fn write_value(output: &mut String, value: &Value, indent: usize) {
+    let padding = "  ".repeat(indent + 1);
+    match value {
+        Value::Table(entries) if !entries.is_empty() => {
+            output.push_str("{\n");
+            for (i, (key, value)) in entries.iter().enumerate() {
+                output.push_str(&padding);
+                output.push_str(&escape_string(key));
+                output.push_str(": ");
+                write_value(output, value, indent + 1);
+                output.push_str(if i + 1 < entries.len() { ",\n" } else { "\n" });
+            }
+            output.push_str(&"  ".repeat(indent));
+            output.push('}');
+        }
+        Value::Array(items) if items.iter().any(|item| matches!(item, Value::Table(_))) => {
+            output.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                output.push_str(&padding);
+                write_inline(output, item);
+                output.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            output.push_str(&"  ".repeat(indent));
+            output.push(']');
+        }
+        _ => write_inline(output, value),
+    }
+}
+
+// Função para exportar uma máquina de Turing em JSON
+pub fn to_json(tm: &TuringMachine) -> String {
+    let mut output = String::new();
+    write_value(&mut output, &machine_to_value(tm), 0);
+    output.push('\n');
+    output
+}
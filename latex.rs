@@ -0,0 +1,351 @@
+// Exportação para LaTeX: o diagrama de estados como uma tikzpicture (biblioteca
+// automata), a função de transição δ como uma tabela e a sequência de configurações de
+// uma execução. O texto gerado é um trecho para ser incluído em um documento que
+// carregue os pacotes amsmath e tikz, com \usetikzlibrary{automata, positioning}.
+
+use std::fmt::Write;
+
+use crate::diagram::{bfs_layers, merged_edges, Edge};
+use crate::run::{RunError, RunOptions, UndecidedReason, Verdict};
+use crate::stepper::{Configuration, Stepper};
+use crate::symbol::is_single_grapheme;
+use crate::{Transition, TuringMachine};
+
+// Número padrão de passos da sequência de configurações exportada. Cada passo ocupa uma
+// linha do documento, por isso o limite é bem menor que o da execução.
+pub const DEFAULT_LATEX_STEPS: u64 = 50;
+
+// Escapa os caracteres especiais do LaTeX para uso em modo matemático
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::new();
+    for symbol in text.chars() {
+        match symbol {
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(symbol);
+            }
+            '\\' => escaped.push_str("\\backslash{}"),
+            '~' => escaped.push_str("\\sim{}"),
+            '^' => escaped.push_str("\\hat{}"),
+            ' ' => escaped.push_str("\\ "),
+            _ => escaped.push(symbol),
+        }
+    }
+    escaped
+}
+
+// Nome de um estado em modo matemático: q0 vira q_{0}; os demais nomes ficam em itálico
+fn state_latex(state: &str) -> String {
+    let mut chars = state.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() && !chars.as_str().is_empty() => {
+            let index = chars.as_str();
+            if index.chars().all(|c| c.is_ascii_digit()) {
+                return format!("{}_{{{}}}", first, index);
+            }
+            format!("\\mathit{{{}}}", escape_latex(state))
+        }
+        _ => format!("\\mathit{{{}}}", escape_latex(state)),
+    }
+}
+
+// Sequência de símbolos da fita em fonte de máquina de escrever. Um espaço fino separa
+// cada símbolo com mais de um grafema dos seus vizinhos.
+fn symbols_latex(symbols: &[String]) -> String {
+    let mut text = String::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        if i > 0 && !(is_single_grapheme(symbol) && is_single_grapheme(&symbols[i - 1])) {
+            text.push_str("\\,");
+        }
+        text.push_str(&escape_latex(symbol));
+    }
+    format!("\\mathtt{{{}}}", text)
+}
+
+// Símbolos lidos ou escritos por uma transição, um por fita, separados por vírgulas
+fn symbol_list_latex(symbols: &[String]) -> String {
+    let symbols: Vec<String> = symbols.iter().map(|symbol| symbols_latex(std::slice::from_ref(symbol))).collect();
+    symbols.join(", ")
+}
+
+// Movimentos de uma transição, um por fita, separados por vírgulas
+fn move_list(transition: &Transition) -> String {
+    let moves: Vec<String> = transition.moves.iter().map(|direction| direction.to_string()).collect();
+    moves.join(", ")
+}
+
+// Rótulo de uma transição no diagrama, como a → X, R
+fn transition_latex(transition: &Transition) -> String {
+    format!(
+        "{} \\to {}, {}",
+        symbol_list_latex(&transition.read_symbols),
+        symbol_list_latex(&transition.write_symbols),
+        move_list(transition)
+    )
+}
+
+// Função para exportar o diagrama de estados como uma tikzpicture. Cada camada da busca
+// em largura a partir do estado inicial ocupa uma coluna do desenho.
+pub fn to_tikz(tm: &TuringMachine) -> String {
+    let layers = bfs_layers(tm);
+    let mut ids: Vec<(&str, String)> = Vec::new();
+    let mut output = String::new();
+    output.push_str("\\begin{tikzpicture}[->, >=stealth, auto, semithick, node distance=3cm]\n");
+
+    for (column, layer) in layers.iter().enumerate() {
+        for (row, &state) in layer.iter().enumerate() {
+            let id = format!("s{}", ids.len());
+            let mut style = String::from("state");
+            if state == tm.initial_state {
+                style.push_str(", initial");
+            }
+            if tm.accept_states.iter().any(|accept| accept == state) {
+                style.push_str(", accepting");
+            }
+            // Centraliza verticalmente os estados de cada camada
+            let y = (layer.len() as f64 - 1.0) - 2.0 * row as f64;
+            writeln!(
+                output,
+                "    \\node[{}] ({}) at ({}, {}) {{${}$}};",
+                style,
+                id,
+                3 * column,
+                y,
+                state_latex(state)
+            )
+            .unwrap();
+            ids.push((state, id));
+        }
+    }
+
+    let id = |state: &str| ids.iter().find(|(name, _)| *name == state).map(|(_, id)| id.as_str()).unwrap();
+    let edges = merged_edges(tm);
+    let transitions: Vec<(&Edge, Vec<&Transition>)> = edges
+        .iter()
+        .map(|edge| {
+            let transitions = tm
+                .transitions
+                .iter()
+                .filter(|transition| transition.from_state == edge.from_state && transition.to_state == edge.to_state)
+                .collect();
+            (edge, transitions)
+        })
+        .collect();
+
+    output.push_str("    \\path\n");
+    for (edge, transitions) in &transitions {
+        let style = if edge.from_state == edge.to_state {
+            "[loop above]"
+        } else if edges.iter().any(|other| other.from_state == edge.to_state && other.to_state == edge.from_state) {
+            // Arestas nos dois sentidos são curvadas para não se sobreporem
+            "[bend left]"
+        } else {
+            ""
+        };
+        let labels: Vec<String> =
+            transitions.iter().map(|transition| format!("${}$", transition_latex(transition))).collect();
+        writeln!(
+            output,
+            "        ({}) edge{} node[align=center] {{{}}} ({})",
+            id(edge.from_state),
+            style,
+            labels.join(" \\\\ "),
+            id(edge.to_state)
+        )
+        .unwrap();
+    }
+    output.push_str("    ;\n");
+    output.push_str("\\end{tikzpicture}\n");
+    output
+}
+
+// Função para exportar a função de transição δ como uma tabela. Com uma fita, cada
+// linha é um estado e cada coluna um símbolo de Γ; o estado inicial é marcado com → e
+// os finais com *. Com mais de uma fita, a tabela lista uma transição por linha.
+pub fn transition_table(tm: &TuringMachine) -> String {
+    let mut output = String::new();
+    if tm.tapes > 1 {
+        output.push_str("\\begin{tabular}{|c|c|c|c|c|}\n\\hline\n");
+        output.push_str("Estado & Lê & Próximo estado & Escreve & Move \\\\\n\\hline\n");
+        for transition in &tm.transitions {
+            writeln!(
+                output,
+                "${}$ & $({})$ & ${}$ & $({})$ & $({})$ \\\\",
+                state_latex(&transition.from_state),
+                symbol_list_latex(&transition.read_symbols),
+                state_latex(&transition.to_state),
+                symbol_list_latex(&transition.write_symbols),
+                move_list(transition)
+            )
+            .unwrap();
+        }
+        output.push_str("\\hline\n\\end{tabular}\n");
+        return output;
+    }
+
+    let symbols = &tm.tape_alphabet;
+    writeln!(output, "\\begin{{tabular}}{{|c|{}}}", "c|".repeat(symbols.len())).unwrap();
+    output.push_str("\\hline\n$\\delta$");
+    for symbol in symbols {
+        write!(output, " & ${}$", symbols_latex(std::slice::from_ref(symbol))).unwrap();
+    }
+    output.push_str(" \\\\\n\\hline\n");
+
+    for state in &tm.states {
+        let mut marker = String::new();
+        if *state == tm.initial_state {
+            marker.push_str("\\rightarrow ");
+        }
+        if tm.accept_states.contains(state) {
+            marker.push_str("{*}");
+        }
+        write!(output, "${}{}$", marker, state_latex(state)).unwrap();
+
+        for symbol in symbols {
+            // Em uma máquina não determinística, a célula pode ter várias transições
+            let cells: Vec<String> = tm
+                .transitions
+                .iter()
+                .filter(|transition| transition.from_state == *state && transition.read_symbols == [symbol.as_str()])
+                .map(|transition| {
+                    format!(
+                        "({}, {}, {})",
+                        state_latex(&transition.to_state),
+                        symbols_latex(&transition.write_symbols),
+                        transition.moves[0]
+                    )
+                })
+                .collect();
+            match cells.len() {
+                0 => output.push_str(" & --"),
+                1 => write!(output, " & ${}$", cells[0]).unwrap(),
+                _ => write!(output, " & $\\{{{}\\}}$", cells.join(", ")).unwrap(),
+            }
+        }
+        output.push_str(" \\\\\n");
+    }
+    output.push_str("\\hline\n\\end{tabular}\n");
+    output
+}
+
+// Uma configuração em modo matemático, com o estado escrito antes do símbolo sob a
+// cabeça, como no arquivo de saída. As fitas de uma máquina de várias fitas são
+// separadas por barras verticais.
+pub fn configuration_latex(configuration: &Configuration) -> String {
+    let tapes: Vec<String> = configuration
+        .tapes
+        .iter()
+        .map(|tape| {
+            let cells: Vec<String> = tape.cells().collect();
+            let (left, right) = cells.split_at(tape.head());
+            let mut text = String::new();
+            if !left.is_empty() {
+                text.push_str(&symbols_latex(left));
+            }
+            text.push_str(&state_latex(&configuration.state));
+            text.push_str(&symbols_latex(right));
+            text
+        })
+        .collect();
+    tapes.join(" \\mid ")
+}
+
+// Função para exportar uma sequência de configurações, ligadas por ⊢, em um ambiente
+// align* com uma configuração por linha
+pub fn configuration_sequence(configurations: &[Configuration]) -> String {
+    let mut output = String::new();
+    output.push_str("\\begin{align*}\n");
+    for (i, configuration) in configurations.iter().enumerate() {
+        let turnstile = if i == 0 { "" } else { "\\vdash " };
+        let end = if i + 1 < configurations.len() { " \\\\" } else { "" };
+        writeln!(output, "    {}& {}{}", turnstile, configuration_latex(configuration), end).unwrap();
+    }
+    output.push_str("\\end{align*}\n");
+    output
+}
+
+// Função para exportar a execução da máquina sobre a palavra de entrada: a sequência de
+// configurações, com no máximo `max_steps` passos, seguida do resultado. Se a execução
+// for interrompida pelo limite, o resultado informa que a palavra não foi decidida.
+pub fn computation_latex(tm: &TuringMachine, input_word: &str, max_steps: u64) -> Result<String, RunError> {
+    let mut stepper = Stepper::new(tm, input_word, &RunOptions::default())?;
+    let mut configurations = vec![stepper.configuration()];
+    configurations.extend(stepper.by_ref().take(max_steps as usize).map(|step| step.configuration));
+    let verdict = stepper.verdict().unwrap_or(Verdict::Undecided(UndecidedReason::StepLimit(max_steps)));
+
+    let mut output = configuration_sequence(&configurations);
+    writeln!(output, "\n\\noindent Resultado: {}.", verdict).unwrap();
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+
+    fn machine() -> TuringMachine {
+        let source = "Q = {q0, q1}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {q1}\ndelta:\n\
+                      (q0, B) -> (q0, B, R)\n(q0, a) -> (q1, a, R)\n";
+        parse_description(source, "test.txt").unwrap()
+    }
+
+    #[test]
+    fn state_diagram() {
+        let expected = r"\begin{tikzpicture}[->, >=stealth, auto, semithick, node distance=3cm]
+    \node[state, initial] (s0) at (0, 0) {$q_{0}$};
+    \node[state, accepting] (s1) at (3, 0) {$q_{1}$};
+    \path
+        (s0) edge[loop above] node[align=center] {$\mathtt{B} \to \mathtt{B}, R$} (s0)
+        (s0) edge node[align=center] {$\mathtt{a} \to \mathtt{a}, R$} (s1)
+    ;
+\end{tikzpicture}
+";
+        assert_eq!(to_tikz(&machine()), expected);
+    }
+
+    #[test]
+    fn transition_table_marks_initial_and_accepting_states() {
+        let expected = r"\begin{tabular}{|c|c|c|}
+\hline
+$\delta$ & $\mathtt{a}$ & $\mathtt{B}$ \\
+\hline
+$\rightarrow q_{0}$ & $(q_{1}, \mathtt{a}, R)$ & $(q_{0}, \mathtt{B}, R)$ \\
+${*}q_{1}$ & -- & -- \\
+\hline
+\end{tabular}
+";
+        assert_eq!(transition_table(&machine()), expected);
+    }
+
+    #[test]
+    fn computation_ends_with_the_verdict() {
+        let expected = r"\begin{align*}
+    & q_{0}\mathtt{BaB} \\
+    \vdash & \mathtt{B}q_{0}\mathtt{aB} \\
+    \vdash & \mathtt{Ba}q_{1}\mathtt{B}
+\end{align*}
+
+\noindent Resultado: aceita.
+";
+        assert_eq!(computation_latex(&machine(), "a", DEFAULT_LATEX_STEPS).unwrap(), expected);
+    }
+
+    #[test]
+    fn computation_cut_off_by_the_step_limit_is_undecided() {
+        let expected = r"\begin{align*}
+    & q_{0}\mathtt{BaB} \\
+    \vdash & \mathtt{B}q_{0}\mathtt{aB}
+\end{align*}
+
+\noindent Resultado: indeterminado: limite de 1 passos atingido.
+";
+        assert_eq!(computation_latex(&machine(), "a", 1).unwrap(), expected);
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape_latex("a_b#{x}"), r"a\_b\#\{x\}");
+        assert_eq!(state_latex("q10"), "q_{10}");
+        assert_eq!(state_latex("fim_1"), r"\mathit{fim\_1}");
+    }
+}
@@ -0,0 +1,36 @@
+// Biblioteca do simulador de máquinas de Turing. Reúne a leitura das descrições, a
+// validação e a execução das máquinas, sem depender de arquivos nem de encerrar o
+// processo; o programa de linha de comando (main.rs) é apenas uma camada sobre ela.
+//
+//     let tm = mt::parse_description(&source, "mt1.txt")?;
+//     let result = mt::run(&tm, "aabb", &mt::RunOptions::default())?;
+//     println!("{} em {} passos", result.verdict, result.steps);
+
+pub mod canonical;
+pub mod compiled;
+pub mod diagram;
+pub mod format;
+pub mod jflap;
+pub mod json;
+pub mod latex;
+pub mod machine;
+pub mod nondeterministic;
+pub mod parser;
+pub mod pattern;
+pub mod run;
+pub mod stepper;
+pub mod symbol;
+pub mod tape;
+pub mod template;
+pub mod toml;
+pub mod tree;
+pub mod validate;
+
+pub use machine::{Move, Transition, TuringMachine};
+pub use nondeterministic::{run_nondeterministic, run_nondeterministic_tree, NondeterministicResult};
+pub use parser::{parse_description, parse_reader, ParseError, ReadError};
+pub use run::{check_run, run, run_traced, AcceptanceMode, RunError, RunOptions, RunResult, UndecidedReason, Verdict};
+pub use stepper::{Configuration, Step, Stepper};
+pub use tape::{format_tape, Tape};
+pub use tree::{ComputationTree, NodeStatus, TreeNode};
+pub use validate::{validate, validate_input_word, InvalidInputSymbol, ValidationError};
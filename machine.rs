@@ -0,0 +1,120 @@
+// Estruturas que descrevem uma máquina de Turing: a sêxtupla (Q, Σ, Γ, δ, q0, F), mais
+// os estados de rejeição, o símbolo branco e o número de fitas.
+
+use std::fmt;
+
+use crate::parser::quote_if_needed;
+
+// Movimento da cabeça de leitura/escrita. Na descrição, L/E movem para a esquerda,
+// R/D para a direita e S/N mantêm a cabeça parada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Left,
+    Right,
+    Stay,
+}
+
+impl Move {
+    pub fn from_symbol(symbol: &str) -> Option<Move> {
+        match symbol {
+            "L" | "E" => Some(Move::Left),
+            "R" | "D" => Some(Move::Right),
+            "S" | "N" => Some(Move::Stay),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Move::Left => 'L',
+            Move::Right => 'R',
+            Move::Stay => 'S',
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+// Estrutura que representa uma transição da máquina de Turing. Em uma máquina de k
+// fitas, a transição lê, escreve e move um símbolo por fita, na ordem das fitas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub from_state: String,
+    pub read_symbols: Vec<String>,
+    pub to_state: String,
+    pub write_symbols: Vec<String>,
+    pub moves: Vec<Move>,
+}
+
+impl Transition {
+    // Número de fitas lidas pela transição
+    pub fn tapes(&self) -> usize {
+        self.read_symbols.len()
+    }
+}
+
+// Exibe a transição no mesmo formato usado no arquivo de descrição, por exemplo
+// (q0, a, B) -> (q1, a, a, R, R) em uma máquina de duas fitas. Os nomes que não
+// poderiam ser lidos de volta como uma palavra aparecem entre aspas.
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}", quote_if_needed(&self.from_state))?;
+        for symbol in &self.read_symbols {
+            write!(f, ", {}", quote_if_needed(symbol))?;
+        }
+        write!(f, ") -> ({}", quote_if_needed(&self.to_state))?;
+        for symbol in &self.write_symbols {
+            write!(f, ", {}", quote_if_needed(symbol))?;
+        }
+        for direction in &self.moves {
+            write!(f, ", {}", direction)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// Estrutura que representa uma máquina de Turing
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuringMachine {
+    pub states: Vec<String>,
+    pub alphabet: Vec<String>,
+    pub tape_alphabet: Vec<String>,
+    pub transitions: Vec<Transition>,
+    pub initial_state: String,
+    pub accept_states: Vec<String>,
+    pub reject_states: Vec<String>,
+    pub blank: String,
+    // Número de fitas; a palavra de entrada é escrita na primeira
+    pub tapes: usize,
+}
+
+// Conjunto no formato {a, b, c} da descrição
+fn set_text(items: &[String]) -> String {
+    let items: Vec<_> = items.iter().map(|item| quote_if_needed(item)).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+// Exibe a máquina no formato rotulado do arquivo de descrição, que pode ser lido de
+// volta pelo analisador. Os estados de rejeição só são escritos quando existem.
+impl fmt::Display for TuringMachine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Q = {}", set_text(&self.states))?;
+        writeln!(f, "Sigma = {}", set_text(&self.alphabet))?;
+        writeln!(f, "Gamma = {}", set_text(&self.tape_alphabet))?;
+        writeln!(f, "q0 = {}", quote_if_needed(&self.initial_state))?;
+        writeln!(f, "F = {}", set_text(&self.accept_states))?;
+        if !self.reject_states.is_empty() {
+            writeln!(f, "R = {}", set_text(&self.reject_states))?;
+        }
+        writeln!(f, "blank = {}", quote_if_needed(&self.blank))?;
+        if self.tapes != 1 {
+            writeln!(f, "tapes = {}", self.tapes)?;
+        }
+        writeln!(f, "delta:")?;
+        for transition in &self.transitions {
+            writeln!(f, "    {}", transition)?;
+        }
+        Ok(())
+    }
+}
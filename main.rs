@@ -1,228 +1,633 @@
 // Este programa lê uma descrição de uma máquina de Turing a partir de um arquivo de
-// configuração, inicializa uma fita com uma palavra de entrada, executa a máquina de 
-// Turing e escreve o resultado em um arquivo de saída. O código está organizado em 
-// funções para facilitar a compreensão e manutenção.
+// configuração, inicializa uma fita com uma palavra de entrada, executa a máquina de
+// Turing e escreve o resultado em um arquivo de saída. A leitura, a validação e a
+// execução ficam na biblioteca (lib.rs); aqui ficam apenas a interpretação da linha de
+// comando, o acesso aos arquivos e os códigos de saída.
 
 #![allow(dead_code)] // Permite códigos não utilizados sem emitir um aviso
 
 use std::env;
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::process;
+use std::time::Duration;
+
+use mt::diagram::DiagramFormat;
+use mt::format::{FormatError, MachineFormat};
+use mt::jflap::JflapError;
+use mt::{AcceptanceMode, ComputationTree, RunError, RunOptions, TuringMachine, ValidationError, Verdict};
+
+// Representação da palavra vazia na linha de comando, além de ""
+const EMPTY_WORD: &str = "ε";
+
+// Códigos de saída do programa, um para cada resultado da execução
+const EXIT_ACCEPTED: i32 = 0;
+const EXIT_ERROR: i32 = 1;
+const EXIT_REJECTED: i32 = 2;
+const EXIT_UNDECIDED: i32 = 3;
+const EXIT_LOOPS: i32 = 4;
+
+fn exit_code(verdict: &Verdict) -> i32 {
+    match verdict {
+        Verdict::Accepted => EXIT_ACCEPTED,
+        Verdict::Rejected => EXIT_REJECTED,
+        Verdict::Undecided(_) => EXIT_UNDECIDED,
+        Verdict::Loops { .. } => EXIT_LOOPS,
+    }
+}
 
-// Estrutura que representa uma transição da máquina de Turing
-struct Transition {
-    from_state: String,
-    read_symbol: char,
-    to_state: String,
-    write_symbol: char,
-    move_direction: char,
-}
-
-// Estrutura que representa uma máquina de Turing
-struct TuringMachine {
-    states: Vec<String>,
-    alphabet: Vec<String>,
-    tape_alphabet: Vec<String>,
-    transitions: Vec<Transition>,
-    initial_state: String,
-    accept_states: Vec<String>,
-}
-
-// Função auxiliar para ler uma lista de uma linha
-fn read_list_from_line(line: &str) -> Vec<String> {
-    line.trim_matches(|c| char::is_ascii_punctuation(&c))
-        .split(',')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
-        .collect()
-}
-
-// Função auxiliar para ler uma transição de uma linha
-fn read_transition_from_line(line: &str) -> Transition {
-    let parts: Vec<&str> = line.split("->").collect();
-    let from_state_symbol: Vec<String> = read_list_from_line(parts[0]);
-    let to_triple: Vec<String> = read_list_from_line(parts[1]);
-
-    let from_state = &from_state_symbol[0];
-    let read_symbol = from_state_symbol[1].chars().next().unwrap();
-
-    let to_state = &to_triple[0];
-    let write_symbol = to_triple[1].chars().next().unwrap();
-    let move_direction = to_triple[2].chars().next().unwrap();
-
-    Transition {
-        from_state: from_state.to_string(),
-        read_symbol,
-        to_state: to_state.to_string(),
-        write_symbol,
-        move_direction,
-    }
-}
-
-// Função para construir uma máquina de Turing a partir de um arquivo de configuração
-fn build_turing_machine(config_file: String) -> TuringMachine {
-    // Abre o arquivo de configuração
-    let file = File::open(config_file).expect("Unable to open the file");
-    let reader = BufReader::new(file);
-
-    let mut line_counter = 1;
-
-    let mut states = Vec::new();
-    let mut alphabet = Vec::new();
-    let mut tape_alphabet = Vec::new();
-    let mut transitions = Vec::new();
-    let mut initial_state = String::new();
-    let mut accept_states = Vec::new();
-
-    // Itera sobre as linhas do arquivo
-    for line in reader.lines() {
-        let line = line.expect("Unable to read line");
-        let line = line.trim();
-        let in_transitions = line.starts_with('(') && line_counter != 1;
-
-        match line_counter {
-            2 => states = read_list_from_line(line),
-            3 => alphabet = read_list_from_line(line),
-            4 => tape_alphabet = read_list_from_line(line),
-            _ if in_transitions => transitions.push(read_transition_from_line(line)),
-            _ => {}
-        }
-
-        if line.starts_with('q') {
-            initial_state = line.trim_matches(',').to_string();
-        } else if line.starts_with('{') && line_counter > 4 {
-            accept_states = read_list_from_line(line);
-        }
-
-        line_counter += 1;
-    }
-
-    // Retorna a máquina de Turing construída
-    TuringMachine {
-        states,
-        alphabet,
-        tape_alphabet,
-        transitions,
-        initial_state,
-        accept_states,
-    }
-}
-
-// Função para formatar a fita da máquina de Turing para exibição
-fn format_tape(tape: &Vec<char>, head_position: usize, current_state: &String) -> String {
-    tape.iter()
-        .enumerate()
-        .map(|(i, &symbol)| {
-            if i == head_position {
-                format!("{{{}}}{}", current_state, symbol)
-            } else if head_position == tape.len() && i == tape.len() - 1 {
-                format!("{}{{{}}}", symbol, current_state)
-            } else {
-                symbol.to_string()
+// Função para construir uma máquina de Turing a partir de um arquivo de configuração,
+// JSON, TOML ou .jff do JFLAP. O formato é o indicado ou, se nenhum for indicado, o da
+// extensão do arquivo. Em caso de erro na descrição, todos os erros encontrados são
+// exibidos e o programa é encerrado.
+fn build_turing_machine(config_file: &str, format: Option<MachineFormat>) -> TuringMachine {
+    let source = fs::read_to_string(config_file).unwrap_or_else(|error| {
+        eprintln!("error: unable to read '{}': {}", config_file, error);
+        process::exit(EXIT_ERROR);
+    });
+
+    let format_error = |error: FormatError| -> ! {
+        match error {
+            FormatError::Syntax { line, column, message } => {
+                eprintln!("{}:{}:{}: error: {}", config_file, line, column, message)
             }
-        })
-        .collect::<String>()
-}
-
-// Função para inicializar a fita da máquina de Turing com a palavra de entrada
-fn initialize_tape(input_word: &str) -> Vec<char> {
-    let mut tape: Vec<char> = vec!['B'];
-    tape.extend(input_word.chars());
-    tape.push('B');
-    tape
-}
-
-// Função para escrever na saída
-fn write_to_output(
-    output_buffer: &mut BufWriter<File>,
-    tape: &Vec<char>,
-    head_position: usize,
-    current_state: &String,
-) {
-    writeln!(
-        output_buffer,
-        "{}",
-        format_tape(&tape, head_position, &current_state)
-    )
-    .expect("Failed to write to output file");
-}
-
-// Função principal para executar a máquina de Turing
-fn run_turing_machine(tm: TuringMachine, input_word: String, output_file: String) {
-    let mut tape = initialize_tape(&input_word);
-    let mut current_state = tm.initial_state.clone();
-    let mut head_position = 0;
-
-    let mut output_buffer =
-        BufWriter::new(File::create(output_file).expect("Failed to create output file"));
-
-    write_to_output(&mut output_buffer, &tape, head_position, &current_state);
-
-    // Loop principal da execução da máquina de Turing
-    loop {
-        let current_symbol = tape[head_position];
-
-        // Procura pela transição correspondente
-        let transition = match tm.transitions.iter().find(|t| {
-            t.from_state == current_state && t.read_symbol == current_symbol
-        }) {
-            Some(transition) => transition,
-            None => {
-                // Se não houver transição, rejeita a entrada
-                writeln!(
-                    &mut output_buffer,
-                    "rejeita"
-                )
-                .expect("Failed to write to output file");
-                break;
+            error => eprintln!("{}: error: {}", config_file, error),
+        }
+        process::exit(EXIT_ERROR);
+    };
+
+    match format.unwrap_or_else(|| MachineFormat::from_path(config_file)) {
+        MachineFormat::Description => mt::parse_description(&source, config_file).unwrap_or_else(|errors| {
+            for error in &errors {
+                eprintln!("{}", error);
             }
-        };
+            eprintln!("{} error(s) found in '{}'", errors.len(), config_file);
+            process::exit(EXIT_ERROR);
+        }),
+        MachineFormat::Json => mt::json::parse_json(&source).unwrap_or_else(|error| format_error(error)),
+        MachineFormat::Toml => mt::toml::parse_toml(&source).unwrap_or_else(|error| format_error(error)),
+        MachineFormat::Jflap => mt::jflap::parse_jff(&source).unwrap_or_else(|error| {
+            match error {
+                JflapError::Xml { line, column, message } => {
+                    eprintln!("{}:{}:{}: error: {}", config_file, line, column, message)
+                }
+                error => eprintln!("{}: error: {}", config_file, error),
+            }
+            process::exit(EXIT_ERROR);
+        }),
+    }
+}
 
-        // Atualiza a fita e o estado
-        tape[head_position] = transition.write_symbol;
-        current_state = transition.to_state.clone();
+// Função para validar a máquina de Turing sem executá-la. Exibe os problemas
+// encontrados e retorna o código de saída correspondente. Uma máquina não
+// determinística pode ter várias transições para o mesmo par (estado, símbolo).
+fn check_turing_machine(tm: &TuringMachine, config_file: &str, nondeterministic: bool) -> i32 {
+    let mut errors = mt::validate(tm);
+    if nondeterministic {
+        errors.retain(|error| !matches!(error, ValidationError::Nondeterministic { .. }));
+    }
+    if errors.is_empty() {
+        println!("{}: ok", config_file);
+        return EXIT_ACCEPTED;
+    }
 
-        // Move a cabeça de leitura/escrita
-        match transition.move_direction {
-            'D' => head_position += 1,
-            'E' => head_position -= 1,
-            _ => panic!("Invalid move direction"),
+    for error in &errors {
+        eprintln!("{}: error: {}", config_file, error);
+    }
+    eprintln!("{} error(s) found in '{}'", errors.len(), config_file);
+    EXIT_ERROR
+}
+
+// Função para exibir um erro de execução. Se a palavra de entrada for inválida, ela é
+// exibida com o símbolo inválido destacado.
+fn report_run_error(error: &RunError, input_word: &str) {
+    eprintln!("error: {}", error);
+    if let RunError::InvalidInput(error) = error {
+        eprintln!("  {}", input_word);
+        eprintln!("  {}^", " ".repeat(error.position - 1));
+    }
+}
+
+// Função para escrever um texto gerado no arquivo indicado ou, sem arquivo, na saída
+// padrão
+fn write_or_print(output_file: Option<&str>, contents: &str) -> i32 {
+    match output_file {
+        Some(output_file) => {
+            if let Err(error) = fs::write(output_file, contents) {
+                eprintln!("error: unable to write '{}': {}", output_file, error);
+                return EXIT_ERROR;
+            }
         }
+        None => print!("{}", contents),
+    }
+    EXIT_ACCEPTED
+}
+
+// Função para gerar o trecho LaTeX com o diagrama de estados e a tabela de transições
+// e, se houver uma palavra de entrada, a sequência de configurações da sua execução,
+// limitada a `max_steps` passos
+fn latex_export(tm: &TuringMachine, input_word: Option<&str>, max_steps: u64) -> Result<String, RunError> {
+    let mut contents = mt::latex::to_tikz(tm);
+    contents.push('\n');
+    contents.push_str(&mt::latex::transition_table(tm));
+
+    if let Some(input_word) = input_word {
+        contents.push('\n');
+        contents.push_str(&mt::latex::computation_latex(tm, input_word, max_steps)?);
+    }
+    Ok(contents)
+}
 
-        // Escreve o estado atual da fita na saída
-        write_to_output(&mut output_buffer, &tape, head_position, &current_state);
+// Função para converter a máquina para o formato indicado, escrevendo-a no arquivo de
+// destino ou, sem arquivo, na saída padrão. O formato rotulado é usado para o arquivo
+// de descrição.
+fn convert_turing_machine(tm: &TuringMachine, format: MachineFormat, target_file: Option<&str>) -> i32 {
+    let contents = match format {
+        MachineFormat::Description => tm.to_string(),
+        MachineFormat::Json => mt::json::to_json(tm),
+        MachineFormat::Toml => mt::toml::to_toml(tm),
+        MachineFormat::Jflap => match mt::jflap::to_jff(tm) {
+            Ok(contents) => contents,
+            Err(error) => {
+                eprintln!("error: unable to convert to JFLAP: {}", error);
+                return EXIT_ERROR;
+            }
+        },
+    };
+    write_or_print(target_file, &contents)
+}
 
-        // Verifica se o estado atual é um estado de aceitação
-        if tm.accept_states.contains(&current_state) {
-            writeln!(
-                &mut output_buffer,
-                "aceita"
-            )
-            .expect("Failed to write to output file");
-            break;
+// Função para formatar os arquivos de descrição no formato canônico, reescrevendo os
+// que mudarem. Com `check`, nenhum arquivo é alterado e o programa apenas informa quais
+// arquivos não estão formatados, terminando com erro se houver algum.
+fn format_files(files: &[String], check: bool) -> i32 {
+    let mut exit_code = EXIT_ACCEPTED;
+    for file in files {
+        if MachineFormat::from_path(file) != MachineFormat::Description {
+            eprintln!("error: '{}' is not a description file", file);
+            exit_code = EXIT_ERROR;
+            continue;
+        }
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("error: unable to read '{}': {}", file, error);
+                exit_code = EXIT_ERROR;
+                continue;
+            }
+        };
+        let formatted = match mt::canonical::format_description(&source, file) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                for error in &errors {
+                    eprintln!("{}", error);
+                }
+                eprintln!("{} error(s) found in '{}'", errors.len(), file);
+                exit_code = EXIT_ERROR;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            eprintln!("{}: not formatted", file);
+            exit_code = EXIT_ERROR;
+        } else if let Err(error) = fs::write(file, formatted) {
+            eprintln!("error: unable to write '{}': {}", file, error);
+            exit_code = EXIT_ERROR;
         }
     }
+    exit_code
 }
 
-// Função principal
-fn main() {
-    // Obtém os argumentos da linha de comando
-    let args: Vec<String> = env::args().collect();
+// Função para criar o arquivo de saída, encerrando o programa em caso de erro
+fn create_output_file(output_file: &str) -> BufWriter<File> {
+    let file = File::create(output_file).unwrap_or_else(|error| {
+        eprintln!("error: unable to create '{}': {}", output_file, error);
+        process::exit(EXIT_ERROR);
+    });
+    BufWriter::new(file)
+}
 
-    // Verifica se o número de argumentos é válido
-    if args.len() != 4 {
-        eprintln!("Usage: cargo run description_file.txt input_word output_file.txt");
-        std::process::exit(1);
+// Arquivo para onde a árvore de computação é exportada: em Graphviz DOT se a extensão
+// for .dot ou .gv, e como texto indentado nos demais casos
+struct TreeExport {
+    file: String,
+    max_depth: Option<u64>,
+}
+
+// Função para escrever a árvore de computação no arquivo indicado
+fn write_tree(tree: &ComputationTree, export: &TreeExport) -> bool {
+    let contents = if export.file.ends_with(".dot") || export.file.ends_with(".gv") {
+        tree.to_dot(export.max_depth)
+    } else {
+        tree.to_text(export.max_depth)
+    };
+    if let Err(error) = fs::write(&export.file, contents) {
+        eprintln!("error: unable to write '{}': {}", export.file, error);
+        return false;
     }
+    true
+}
 
-    // Obtém os nomes dos arquivos de entrada e saída
-    let machine_file = args[1].to_string();
-    let input_word = args[2].to_string();
-    let output_file = args[3].to_string();
+// Função para executar a máquina de Turing não determinística e escrever no arquivo de
+// saída a computação que leva à aceitação (ou ao laço), seguida do veredito. O número
+// de configurações exploradas é exibido na saída padrão.
+fn run_nondeterministic_turing_machine(
+    tm: &TuringMachine,
+    input_word: &str,
+    output_file: &str,
+    options: &RunOptions,
+    trace: bool,
+    tree_export: Option<&TreeExport>,
+) -> i32 {
+    if let Err(error) = mt::check_run(tm, input_word, options) {
+        report_run_error(&error, input_word);
+        return EXIT_ERROR;
+    }
+    let mut output_buffer = create_output_file(output_file);
+
+    let result = match tree_export {
+        Some(_) => mt::run_nondeterministic_tree(tm, input_word, options).map(|(result, tree)| (result, Some(tree))),
+        None => mt::run_nondeterministic(tm, input_word, options).map(|result| (result, None)),
+    };
+    let result = result.and_then(|(result, tree)| {
+        if trace {
+            for configuration in &result.path {
+                writeln!(output_buffer, "{}", configuration)?;
+            }
+        }
+        writeln!(output_buffer, "{}", result.verdict)?;
+        output_buffer.flush()?;
+        Ok((result, tree))
+    });
+
+    match result {
+        Ok((result, tree)) => {
+            println!("{} configurações exploradas", result.explored);
+            if let (Some(tree), Some(export)) = (tree, tree_export) {
+                if !write_tree(&tree, export) {
+                    return EXIT_ERROR;
+                }
+            }
+            exit_code(&result.verdict)
+        }
+        Err(error) => {
+            report_run_error(&error, input_word);
+            EXIT_ERROR
+        }
+    }
+}
 
-    // Constrói a máquina de Turing a partir do arquivo de configuração
-    let turing_machine = build_turing_machine(machine_file);
+// Função para executar a máquina de Turing e escrever o resultado no arquivo de saída.
+// Sem `trace`, apenas o veredito é escrito.
+fn run_turing_machine(
+    tm: &TuringMachine,
+    input_word: &str,
+    output_file: &str,
+    options: &RunOptions,
+    trace: bool,
+) -> i32 {
+    // Valida a execução antes de criar o arquivo de saída
+    if let Err(error) = mt::check_run(tm, input_word, options) {
+        report_run_error(&error, input_word);
+        return EXIT_ERROR;
+    }
+    let mut output_buffer = create_output_file(output_file);
+
+    let result = if trace {
+        mt::run_traced(tm, input_word, options, &mut output_buffer)
+    } else {
+        mt::run(tm, input_word, options).and_then(|result| {
+            writeln!(output_buffer, "{}", result.verdict)?;
+            Ok(result)
+        })
+    };
+    let result = result.and_then(|result| {
+        output_buffer.flush()?;
+        Ok(result)
+    });
+
+    match result {
+        Ok(result) => exit_code(&result.verdict),
+        Err(error) => {
+            report_run_error(&error, input_word);
+            EXIT_ERROR
+        }
+    }
+}
 
-    // Executa a máquina de Turing com a palavra de entrada e escreve o resultado no arquivo de saída
-    run_turing_machine(turing_machine, input_word, output_file);
+// Comandos aceitos na linha de comando
+enum Command {
+    Run {
+        machine_file: String,
+        input_word: String,
+        output_file: String,
+        options: RunOptions,
+        trace: bool,
+        nondeterministic: bool,
+        tree_export: Option<TreeExport>,
+    },
+    Check {
+        machine_file: String,
+        nondeterministic: bool,
+    },
+    Diagram {
+        format: DiagramFormat,
+        machine_file: String,
+        output_file: Option<String>,
+    },
+    Latex {
+        machine_file: String,
+        input_word: Option<String>,
+        max_steps: u64,
+        output_file: Option<String>,
+    },
+    Fmt {
+        files: Vec<String>,
+        check: bool,
+    },
+    Convert {
+        source_file: String,
+        target_file: Option<String>,
+        target_format: MachineFormat,
+    },
+    Expand {
+        machine_file: String,
+        output_file: Option<String>,
+    },
+}
+
+const USAGE: &str = "Usage: mt [--accept-mode entry|halt|sipser] [--max-steps N|none] [--time-limit SECONDS] [--detect-loops] \
+                     [--separator SEP] [--no-trace] [--nondeterministic] [--max-configurations N|none] \
+                     [--tree tree_file.dot|tree_file.txt] [--tree-depth N] description_file.txt input_word output_file.txt
+       mt check [--nondeterministic] description_file.txt
+       mt dot|mermaid|plantuml description_file.txt [diagram_file]
+       mt latex [--word input_word] [--max-steps N] description_file.txt [latex_file.tex]
+       mt convert [--to description|json|toml|jff] source_file [target_file]
+       mt fmt [--check] description_file.txt...
+       mt expand description_file.txt [output_file.txt]
+
+The machine file format is taken from its extension (.json, .toml, .jff, anything else is a description file) \
+unless --format description|json|toml|jff is given.";
+
+// Função para interpretar os argumentos da linha de comando. A opção --format vale para
+// todos os comandos e indica o formato do arquivo da máquina.
+fn parse_arguments(args: &[String]) -> Result<(Command, Option<MachineFormat>), String> {
+    let mut args = args.to_vec();
+    let mut format = None;
+    if let Some(index) = args.iter().position(|arg| arg == "--format") {
+        let value = args.get(index + 1).ok_or("missing value for --format")?;
+        format = Some(MachineFormat::from_name(value).ok_or_else(|| format!("invalid format '{}'", value))?);
+        args.drain(index..index + 2);
+    }
+    Ok((parse_command(&args)?, format))
+}
+
+// Função para interpretar o comando e as suas opções
+fn parse_command(args: &[String]) -> Result<Command, String> {
+    if args.first().map(String::as_str) == Some("check") {
+        return match &args[1..] {
+            [machine_file] => Ok(Command::Check { machine_file: machine_file.to_string(), nondeterministic: false }),
+            [flag, machine_file] if flag == "--nondeterministic" => {
+                Ok(Command::Check { machine_file: machine_file.to_string(), nondeterministic: true })
+            }
+            _ => Err(USAGE.to_string()),
+        };
+    }
+    if args.first().map(String::as_str) == Some("latex") {
+        let mut input_word = None;
+        let mut max_steps = mt::latex::DEFAULT_LATEX_STEPS;
+        let mut positional = Vec::new();
+        let mut args = args[1..].iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--word" => input_word = Some(args.next().ok_or("missing value for --word")?.to_string()),
+                "--max-steps" => {
+                    let value = args.next().ok_or("missing value for --max-steps")?;
+                    max_steps = value.parse().map_err(|_| format!("invalid step limit '{}'", value))?;
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+                _ => positional.push(arg.to_string()),
+            }
+        }
+        let mut positional = positional.into_iter();
+        return match (positional.next(), positional.next(), positional.next()) {
+            (Some(machine_file), output_file, None) => {
+                Ok(Command::Latex { machine_file, input_word, max_steps, output_file })
+            }
+            _ => Err(USAGE.to_string()),
+        };
+    }
+    if args.first().map(String::as_str) == Some("fmt") {
+        let mut check = false;
+        let mut files = Vec::new();
+        for arg in &args[1..] {
+            match arg.as_str() {
+                "--check" => check = true,
+                _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+                _ => files.push(arg.to_string()),
+            }
+        }
+        if files.is_empty() {
+            return Err(USAGE.to_string());
+        }
+        return Ok(Command::Fmt { files, check });
+    }
+    if args.first().map(String::as_str) == Some("convert") {
+        let mut target_format = None;
+        let mut positional = Vec::new();
+        let mut args = args[1..].iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--to" => {
+                    let value = args.next().ok_or("missing value for --to")?;
+                    target_format =
+                        Some(MachineFormat::from_name(value).ok_or_else(|| format!("invalid format '{}'", value))?);
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+                _ => positional.push(arg.to_string()),
+            }
+        }
+        let mut positional = positional.into_iter();
+        return match (positional.next(), positional.next(), positional.next()) {
+            (Some(source_file), target_file, None) => {
+                // Sem --to, o formato de destino vem da extensão do arquivo de destino
+                let target_format = match (target_format, &target_file) {
+                    (Some(format), _) => format,
+                    (None, Some(target_file)) => MachineFormat::from_path(target_file),
+                    (None, None) => return Err("convert requires a target file or --to".to_string()),
+                };
+                Ok(Command::Convert { source_file, target_file, target_format })
+            }
+            _ => Err(USAGE.to_string()),
+        };
+    }
+    if args.first().map(String::as_str) == Some("expand") {
+        return match &args[1..] {
+            [machine_file] => Ok(Command::Expand { machine_file: machine_file.to_string(), output_file: None }),
+            [machine_file, output_file] => Ok(Command::Expand {
+                machine_file: machine_file.to_string(),
+                output_file: Some(output_file.to_string()),
+            }),
+            _ => Err(USAGE.to_string()),
+        };
+    }
+    if let Some(format) = args.first().and_then(|name| DiagramFormat::from_name(name)) {
+        return match &args[1..] {
+            [machine_file] => Ok(Command::Diagram { format, machine_file: machine_file.to_string(), output_file: None }),
+            [machine_file, output_file] => Ok(Command::Diagram {
+                format,
+                machine_file: machine_file.to_string(),
+                output_file: Some(output_file.to_string()),
+            }),
+            _ => Err(USAGE.to_string()),
+        };
+    }
+
+    let mut options = RunOptions::default();
+    let mut trace = true;
+    let mut nondeterministic = false;
+    let mut tree_file = None;
+    let mut tree_depth = None;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--accept-mode" => {
+                let value = args.next().ok_or("missing value for --accept-mode")?;
+                options.acceptance = AcceptanceMode::from_name(value)
+                    .ok_or_else(|| format!("invalid acceptance mode '{}'", value))?;
+            }
+            "--max-steps" => {
+                let value = args.next().ok_or("missing value for --max-steps")?;
+                options.max_steps = match value.as_str() {
+                    "none" => None,
+                    _ => Some(value.parse().map_err(|_| format!("invalid step limit '{}'", value))?),
+                };
+            }
+            "--time-limit" => {
+                let value = args.next().ok_or("missing value for --time-limit")?;
+                let seconds: f64 = value
+                    .parse()
+                    .ok()
+                    .filter(|seconds: &f64| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or_else(|| format!("invalid time limit '{}'", value))?;
+                options.time_limit = Some(Duration::from_secs_f64(seconds));
+            }
+            "--detect-loops" => options.detect_loops = true,
+            "--separator" => {
+                let value = args.next().ok_or("missing value for --separator")?;
+                if value.is_empty() {
+                    return Err("the separator cannot be empty".to_string());
+                }
+                options.separator = Some(value.to_string());
+            }
+            "--no-trace" => trace = false,
+            "--nondeterministic" => nondeterministic = true,
+            "--tree" => tree_file = Some(args.next().ok_or("missing value for --tree")?.to_string()),
+            "--tree-depth" => {
+                let value = args.next().ok_or("missing value for --tree-depth")?;
+                tree_depth = Some(value.parse().map_err(|_| format!("invalid tree depth '{}'", value))?);
+            }
+            "--max-configurations" => {
+                let value = args.next().ok_or("missing value for --max-configurations")?;
+                options.max_configurations = match value.as_str() {
+                    "none" => None,
+                    _ => Some(value.parse().map_err(|_| format!("invalid configuration limit '{}'", value))?),
+                };
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+            _ => positional.push(arg.to_string()),
+        }
+    }
+
+    if tree_file.is_some() && !nondeterministic {
+        return Err("--tree requires --nondeterministic".to_string());
+    }
+    if tree_depth.is_some() && tree_file.is_none() {
+        return Err("--tree-depth requires --tree".to_string());
+    }
+    let tree_export = tree_file.map(|file| TreeExport { file, max_depth: tree_depth });
+
+    match <[String; 3]>::try_from(positional) {
+        Ok([machine_file, input_word, output_file]) => Ok(Command::Run {
+            machine_file,
+            input_word,
+            output_file,
+            options,
+            trace,
+            nondeterministic,
+            tree_export,
+        }),
+        Err(_) => Err(USAGE.to_string()),
+    }
+}
+
+// Função principal
+fn main() {
+    // Obtém os argumentos da linha de comando
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Verifica se os argumentos são válidos
+    let (command, machine_format) = parse_arguments(&args).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(EXIT_ERROR);
+    });
+
+    let exit_code = match command {
+        Command::Run { machine_file, input_word, output_file, options, trace, nondeterministic, tree_export } => {
+            // Constrói a máquina de Turing a partir do arquivo de configuração
+            let turing_machine = build_turing_machine(&machine_file, machine_format);
+
+            // A palavra de entrada pode ser a palavra vazia
+            let input_word = if input_word == EMPTY_WORD { "" } else { input_word.as_str() };
+
+            // Executa a máquina de Turing com a palavra de entrada e escreve o resultado no arquivo de saída
+            if nondeterministic {
+                run_nondeterministic_turing_machine(
+                    &turing_machine,
+                    input_word,
+                    &output_file,
+                    &options,
+                    trace,
+                    tree_export.as_ref(),
+                )
+            } else {
+                run_turing_machine(&turing_machine, input_word, &output_file, &options, trace)
+            }
+        }
+        Command::Check { machine_file, nondeterministic } => {
+            let turing_machine = build_turing_machine(&machine_file, machine_format);
+            check_turing_machine(&turing_machine, &machine_file, nondeterministic)
+        }
+        Command::Diagram { format, machine_file, output_file } => {
+            let turing_machine = build_turing_machine(&machine_file, machine_format);
+            write_or_print(output_file.as_deref(), &format.render(&turing_machine))
+        }
+        Command::Latex { machine_file, input_word, max_steps, output_file } => {
+            let turing_machine = build_turing_machine(&machine_file, machine_format);
+            let input_word = input_word.as_deref().map(|word| if word == EMPTY_WORD { "" } else { word });
+            match latex_export(&turing_machine, input_word, max_steps) {
+                Ok(contents) => write_or_print(output_file.as_deref(), &contents),
+                Err(error) => {
+                    report_run_error(&error, input_word.unwrap_or_default());
+                    EXIT_ERROR
+                }
+            }
+        }
+        Command::Fmt { files, check } => format_files(&files, check),
+        Command::Convert { source_file, target_file, target_format } => {
+            let turing_machine = build_turing_machine(&source_file, machine_format);
+            convert_turing_machine(&turing_machine, target_format, target_file.as_deref())
+        }
+        Command::Expand { machine_file, output_file } => {
+            // A máquina já vem com os blocos for e os padrões expandidos
+            let turing_machine = build_turing_machine(&machine_file, machine_format);
+            write_or_print(output_file.as_deref(), &mt::canonical::format_machine(&turing_machine))
+        }
+    };
+    process::exit(exit_code);
 }
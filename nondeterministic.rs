@@ -0,0 +1,380 @@
+// Simulação de máquinas de Turing não determinísticas. Quando há mais de uma transição
+// para o mesmo par (estado, símbolos lidos), todas as ramificações da computação são
+// exploradas em largura: a palavra é aceita se alguma ramificação aceitar, e rejeitada
+// se todas pararem sem aceitar. Como a árvore de computação pode crescer
+// exponencialmente, a exploração é limitada pelo número de passos de cada ramificação
+// e pelo número total de configurações.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use crate::compiled::{CompiledMachine, CompiledTransition, StateId, SymbolId};
+use crate::run::{check_run, RunError, RunOptions, UndecidedReason, Verdict};
+use crate::stepper::{configuration_verdict, initial_tapes, read_symbols, to_configuration, Configuration};
+use crate::tape::Tape;
+use crate::tree::{ComputationTree, NodeStatus, TreeNode};
+use crate::{Transition, TuringMachine};
+
+// Resultado da simulação não determinística
+#[derive(Debug)]
+pub struct NondeterministicResult {
+    pub verdict: Verdict,
+    // Número de configurações distintas geradas, incluindo a inicial
+    pub explored: u64,
+    // Computação a partir da configuração inicial que leva à aceitação ou, se houver
+    // um laço infinito, que percorre o laço uma vez; vazia nos demais casos
+    pub path: Vec<Configuration>,
+}
+
+// Configuração na árvore de computação, com o índice da configuração que a gerou e a
+// transição usada para chegar a ela. A situação só deixa de ser não explorada quando a
+// configuração é retirada da fila e avaliada.
+struct Node<'m> {
+    state: StateId,
+    tapes: Vec<Tape<SymbolId>>,
+    parent: Option<(usize, &'m CompiledTransition)>,
+    depth: u64,
+    status: NodeStatus,
+}
+
+// Resultado da exploração, antes da conversão das configurações
+struct Exploration<'m> {
+    verdict: Verdict,
+    nodes: Vec<Node<'m>>,
+    path: Vec<usize>,
+    // Transições que levam a configurações já geradas, com `detect_loops`: a
+    // configuração de origem, a repetida e a transição usada
+    repeated: Vec<(usize, usize, &'m CompiledTransition)>,
+}
+
+// Índices das configurações do caminho da configuração inicial até `index`
+fn path_to(nodes: &[Node], index: usize) -> Vec<usize> {
+    let mut path = vec![index];
+    while let Some((parent, _)) = nodes[*path.last().unwrap()].parent {
+        path.push(parent);
+    }
+    path.reverse();
+    path
+}
+
+// Função que procura um ciclo no grafo de configurações, formado pelas arestas da
+// árvore e pelas arestas que levam a configurações repetidas. Devolve o caminho da
+// configuração inicial até o ciclo, seguido do ciclo, e o comprimento do ciclo.
+fn find_cycle(nodes: &[Node], repeated: &[(usize, usize, &CompiledTransition)]) -> Option<(Vec<usize>, u64)> {
+    let mut successors = vec![Vec::new(); nodes.len()];
+    for (index, node) in nodes.iter().enumerate() {
+        if let Some((parent, _)) = node.parent {
+            successors[parent].push(index);
+        }
+    }
+    for &(from, to, _) in repeated {
+        successors[from].push(to);
+    }
+
+    // Busca em profundidade iterativa; `position` guarda a posição de cada configuração
+    // na pilha enquanto ela está sendo visitada
+    let mut position: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut done = vec![false; nodes.len()];
+    let mut stack = vec![(0, 0)];
+    position[0] = Some(0);
+    while let Some(&mut (index, ref mut next)) = stack.last_mut() {
+        let Some(&successor) = successors[index].get(*next) else {
+            position[index] = None;
+            done[index] = true;
+            stack.pop();
+            continue;
+        };
+        *next += 1;
+        if let Some(start) = position[successor] {
+            let mut path = path_to(nodes, successor);
+            path.extend(stack[start + 1..].iter().map(|&(index, _)| index));
+            path.push(successor);
+            return Some((path, (stack.len() - start) as u64));
+        }
+        if !done[successor] {
+            position[successor] = Some(stack.len());
+            stack.push((successor, 0));
+        }
+    }
+    None
+}
+
+// Explora em largura todas as ramificações da computação da máquina sobre a palavra de
+// entrada. Os limites de `options` valem para a exploração como um todo: `max_steps`
+// limita a profundidade de cada ramificação e `max_configurations` o número de
+// configurações geradas. Com `detect_loops`, configurações repetidas não são
+// exploradas de novo, e a máquina entra em laço se o grafo de configurações tiver um
+// ciclo.
+fn explore<'m>(machine: &'m CompiledMachine, input_symbols: &[String], options: &RunOptions) -> Exploration<'m> {
+    let root = Node {
+        state: machine.initial_state(),
+        tapes: initial_tapes(machine, input_symbols),
+        parent: None,
+        depth: 0,
+        status: NodeStatus::Unexplored,
+    };
+    let mut visited = HashMap::new();
+    if options.detect_loops {
+        let normalized: Vec<_> = root.tapes.iter().map(Tape::normalized).collect();
+        visited.insert((root.state, normalized), 0);
+    }
+    let mut nodes = vec![root];
+    let mut queue = VecDeque::from([0]);
+    let mut repeated = Vec::new();
+    let mut cut_off = None;
+    let mut symbols = Vec::new();
+    let start_time = Instant::now();
+    // Número de configurações retiradas da fila, usado para consultar o relógio apenas
+    // de tempos em tempos
+    let mut popped: u64 = 0;
+
+    let (verdict, path) = 'explore: loop {
+        let Some(index) = queue.pop_front() else {
+            // Todas as ramificações foram exploradas sem aceitar a palavra
+            if let Some(reason) = cut_off {
+                break (Verdict::Undecided(reason), Vec::new());
+            }
+            match find_cycle(&nodes, &repeated) {
+                Some((path, period)) => {
+                    // O caminho passa pela configuração repetida duas vezes, `period` passos
+                    // separadas
+                    let first_step = path.len() as u64 - period - 1;
+                    break (Verdict::Loops { first_step, period }, path);
+                }
+                None => break (Verdict::Rejected, Vec::new()),
+            }
+        };
+
+        popped += 1;
+        if let Some(time_limit) = options.time_limit {
+            if popped.is_multiple_of(1024) && start_time.elapsed() >= time_limit {
+                break (Verdict::Undecided(UndecidedReason::TimeLimit(time_limit)), Vec::new());
+            }
+        }
+
+        // Uma ramificação que aceita encerra a exploração; as que rejeitam terminam ali
+        let node = &nodes[index];
+        read_symbols(&node.tapes, &mut symbols);
+        let transitions = machine.transitions(node.state, &symbols);
+        match configuration_verdict(machine, node.state, !transitions.is_empty(), options.acceptance) {
+            Some(Verdict::Accepted) => {
+                nodes[index].status = NodeStatus::Accepting;
+                break (Verdict::Accepted, path_to(&nodes, index));
+            }
+            Some(_) => {
+                nodes[index].status = NodeStatus::Rejecting;
+                continue;
+            }
+            None => {}
+        }
+
+        if options.max_steps.is_some_and(|max_steps| node.depth >= max_steps) {
+            cut_off = Some(UndecidedReason::StepLimit(node.depth));
+            continue;
+        }
+
+        // Gera uma configuração para cada transição possível
+        let mut children = Vec::new();
+        for transition in transitions {
+            let mut tapes = node.tapes.clone();
+            transition.apply(&mut tapes);
+            children.push(Node {
+                state: transition.to_state,
+                tapes,
+                parent: Some((index, transition)),
+                depth: node.depth + 1,
+                status: NodeStatus::Unexplored,
+            });
+        }
+        nodes[index].status = NodeStatus::Expanded;
+
+        for child in children {
+            if options.detect_loops {
+                let normalized: Vec<_> = child.tapes.iter().map(Tape::normalized).collect();
+                match visited.entry((child.state, normalized)) {
+                    Entry::Occupied(entry) => {
+                        let transition = child.parent.map(|(_, transition)| transition).unwrap();
+                        repeated.push((index, *entry.get(), transition));
+                        continue;
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(nodes.len());
+                    }
+                }
+            }
+
+            if let Some(max_configurations) = options.max_configurations {
+                if nodes.len() as u64 >= max_configurations {
+                    let reason = UndecidedReason::ConfigurationLimit(max_configurations);
+                    break 'explore (Verdict::Undecided(reason), Vec::new());
+                }
+            }
+            queue.push_back(nodes.len());
+            nodes.push(child);
+        }
+    };
+
+    Exploration { verdict, nodes, path, repeated }
+}
+
+impl Exploration<'_> {
+    fn result(&self, machine: &CompiledMachine) -> NondeterministicResult {
+        NondeterministicResult {
+            verdict: self.verdict.clone(),
+            explored: self.nodes.len() as u64,
+            path: self
+                .path
+                .iter()
+                .map(|&index| to_configuration(machine, self.nodes[index].state, &self.nodes[index].tapes))
+                .collect(),
+        }
+    }
+
+    // Transição usada a partir da configuração `from`, com os nomes dos estados e símbolos
+    fn transition(&self, machine: &CompiledMachine, from: usize, transition: &CompiledTransition) -> Transition {
+        let from = &self.nodes[from];
+        Transition {
+            from_state: machine.state_name(from.state).to_string(),
+            read_symbols: from.tapes.iter().map(|tape| machine.symbol(tape.read()).to_string()).collect(),
+            to_state: machine.state_name(transition.to_state).to_string(),
+            write_symbols: transition.write_symbols.iter().map(|&symbol| machine.symbol(symbol).to_string()).collect(),
+            moves: transition.moves.clone(),
+        }
+    }
+
+    // Converte as configurações geradas em uma árvore de computação, com as transições
+    // que levam a configurações repetidas como arestas de retorno
+    fn tree(&self, machine: &CompiledMachine) -> ComputationTree {
+        let mut nodes: Vec<TreeNode> = Vec::with_capacity(self.nodes.len());
+        for (index, node) in self.nodes.iter().enumerate() {
+            let parent = node.parent.map(|(parent, transition)| {
+                nodes[parent].children.push(index);
+                (parent, self.transition(machine, parent, transition))
+            });
+            nodes.push(TreeNode {
+                configuration: to_configuration(machine, node.state, &node.tapes).to_string(),
+                parent,
+                children: Vec::new(),
+                repeats: Vec::new(),
+                depth: node.depth,
+                status: node.status,
+            });
+        }
+        for &(from, to, transition) in &self.repeated {
+            let transition = self.transition(machine, from, transition);
+            nodes[from].repeats.push((to, transition));
+        }
+        ComputationTree { nodes }
+    }
+}
+
+// Simula a máquina não determinística sobre a palavra de entrada
+pub fn run_nondeterministic(
+    tm: &TuringMachine,
+    input_word: &str,
+    options: &RunOptions,
+) -> Result<NondeterministicResult, RunError> {
+    let input_symbols = check_run(tm, input_word, options)?;
+    let machine = CompiledMachine::compile(tm);
+    Ok(explore(&machine, &input_symbols, options).result(&machine))
+}
+
+// Simula a máquina não determinística e devolve também a árvore com todas as
+// configurações geradas durante a exploração
+pub fn run_nondeterministic_tree(
+    tm: &TuringMachine,
+    input_word: &str,
+    options: &RunOptions,
+) -> Result<(NondeterministicResult, ComputationTree), RunError> {
+    let input_symbols = check_run(tm, input_word, options)?;
+    let machine = CompiledMachine::compile(tm);
+    let exploration = explore(&machine, &input_symbols, options);
+    Ok((exploration.result(&machine), exploration.tree(&machine)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+
+    // Máquina com Q = {q0, q1, q2, q3}, aceitação em q2 e rejeição em q1
+    fn machine(transitions: &str) -> TuringMachine {
+        let source = format!(
+            "Q = {{q0, q1, q2, q3}}\nSigma = {{a, b}}\nGamma = {{a, b, B}}\nq0 = q0\nF = {{q2}}\nR = {{q1}}\n\
+             delta:\n{}",
+            transitions
+        );
+        parse_description(&source, "test.txt").unwrap()
+    }
+
+    fn statuses(tree: &ComputationTree) -> Vec<NodeStatus> {
+        tree.nodes.iter().map(|node| node.status).collect()
+    }
+
+    #[test]
+    fn accepts_if_some_branch_accepts() {
+        let tm = machine("(q0, B) -> (q1, B, R)\n(q0, B) -> (q2, B, R)\n");
+        let (result, tree) = run_nondeterministic_tree(&tm, "a", &RunOptions::default()).unwrap();
+        assert_eq!(result.verdict, Verdict::Accepted);
+        assert_eq!(result.explored, 3);
+        let path: Vec<String> = result.path.iter().map(ToString::to_string).collect();
+        assert_eq!(path, [tree.nodes[0].configuration.clone(), tree.nodes[2].configuration.clone()]);
+        assert_eq!(statuses(&tree), [NodeStatus::Expanded, NodeStatus::Rejecting, NodeStatus::Accepting]);
+    }
+
+    #[test]
+    fn rejects_if_every_branch_halts_without_accepting() {
+        // A ramificação em q3 para sem transição para (q3, a)
+        let tm = machine("(q0, B) -> (q1, B, R)\n(q0, B) -> (q3, B, R)\n");
+        let (result, tree) = run_nondeterministic_tree(&tm, "a", &RunOptions::default()).unwrap();
+        assert_eq!(result.verdict, Verdict::Rejected);
+        assert!(result.path.is_empty());
+        assert_eq!(statuses(&tree), [NodeStatus::Expanded, NodeStatus::Rejecting, NodeStatus::Rejecting]);
+    }
+
+    #[test]
+    fn accepting_branch_leaves_the_rest_of_the_queue_unexplored() {
+        let tm = machine("(q0, B) -> (q2, B, R)\n(q0, B) -> (q3, B, R)\n(q3, a) -> (q3, a, R)\n");
+        let (result, tree) = run_nondeterministic_tree(&tm, "a", &RunOptions::default()).unwrap();
+        assert_eq!(result.verdict, Verdict::Accepted);
+        assert_eq!(statuses(&tree), [NodeStatus::Expanded, NodeStatus::Accepting, NodeStatus::Unexplored]);
+    }
+
+    #[test]
+    fn configuration_limit_leaves_the_queue_unexplored() {
+        let tm = machine("(q0, B) -> (q0, B, R)\n(q0, B) -> (q0, a, R)\n(q0, a) -> (q0, a, R)\n");
+        let options = RunOptions { max_configurations: Some(3), ..RunOptions::default() };
+        let (result, tree) = run_nondeterministic_tree(&tm, "", &options).unwrap();
+        assert_eq!(result.verdict, Verdict::Undecided(UndecidedReason::ConfigurationLimit(3)));
+        assert_eq!(result.explored, 3);
+        assert_eq!(tree.nodes[2].status, NodeStatus::Unexplored);
+    }
+
+    #[test]
+    fn step_limit_cuts_every_branch() {
+        let tm = machine("(q0, B) -> (q0, B, R)\n(q0, B) -> (q0, a, R)\n(q0, a) -> (q0, a, R)\n");
+        let options = RunOptions { max_steps: Some(2), ..RunOptions::default() };
+        let result = run_nondeterministic(&tm, "", &options).unwrap();
+        assert_eq!(result.verdict, Verdict::Undecided(UndecidedReason::StepLimit(2)));
+        assert_eq!(result.explored, 7);
+    }
+
+    #[test]
+    fn repeated_configurations_are_back_edges_of_a_loop() {
+        // De Ba{q0}... a máquina pode voltar para a configuração inicial ou parar em q3
+        let tm = machine("(q0, B) -> (q0, B, R)\n(q0, a) -> (q0, a, L)\n(q0, a) -> (q3, a, S)\n");
+        let options = RunOptions { detect_loops: true, ..RunOptions::default() };
+        let (result, tree) = run_nondeterministic_tree(&tm, "a", &options).unwrap();
+        assert_eq!(result.verdict, Verdict::Loops { first_step: 0, period: 2 });
+        assert_eq!(result.path.len(), 3);
+        assert_eq!(result.path[0], result.path[2]);
+
+        assert_eq!(tree.nodes.len(), 3);
+        assert_eq!(tree.nodes[1].children, [2]);
+        let (target, transition) = &tree.nodes[1].repeats[0];
+        assert_eq!(*target, 0);
+        assert_eq!(transition.to_string(), "(q0, a) -> (q0, a, L)");
+        assert_eq!(statuses(&tree), [NodeStatus::Expanded, NodeStatus::Expanded, NodeStatus::Rejecting]);
+    }
+}
@@ -0,0 +1,1272 @@
+// Analisador do arquivo de descrição da máquina de Turing. Em vez de interromper o
+// programa no primeiro problema, o analisador acumula todos os erros encontrados,
+// cada um com o arquivo, a linha e a coluna onde ocorreu, e os devolve ao chamador.
+//
+// São aceitos dois formatos. O formato rotulado identifica cada parte da sêxtupla
+// pelo nome, em qualquer ordem:
+//
+//     Q = {q0, q1}
+//     Sigma = {a}
+//     Gamma = {a, B}
+//     delta:
+//         (q0, a) -> (q1, a, D)
+//     q0 = q0
+//     F = {q1}
+//     R = {}
+//     blank = B
+//
+// As seções R (estados de rejeição) e blank são opcionais. O formato posicional, usado
+// pelas descrições antigas, lista as partes na ordem da sêxtupla:
+// ({...}, {...}, {...}, {transições}, q0, {...}), opcionalmente seguidas do conjunto de
+// estados de rejeição e do símbolo branco. Em ambos os formatos, o branco padrão é B, e
+// linhas em branco e comentários iniciados por // são ignorados.
+//
+// Uma transição pode ler um conjunto de símbolos, como em (q0, {a, b}) -> (q0, =, R), ou
+// o curinga *, que vale para os símbolos de Γ sem uma transição mais específica; = no
+// lugar do símbolo escrito escreve de volta o símbolo lido. Os padrões são expandidos em
+// transições comuns ao final da análise (veja pattern.rs).
+//
+// Entre as transições, um bloco for x in {a, b} { ... } repete as transições do corpo
+// para cada valor de x, trocando x e cada [x] dos nomes pelo valor, como em carry[x];
+// os estados gerados são acrescentados a Q (veja template.rs).
+//
+// Em uma máquina de k fitas, cada transição lê k símbolos e escreve k símbolos e k
+// movimentos, como em (q0, a, B) -> (q1, a, a, R, R). O número de fitas é deduzido das
+// transições, que devem concordar entre si e com a seção opcional tapes = k, se houver.
+//
+// Estados e símbolos são palavras quaisquer, como q_inicio, X1 ou #a. Um nome com
+// espaços, delimitadores ou aspas, ou o símbolo *, é escrito entre aspas duplas, com \"
+// e \\ para a aspa e a barra invertida: Gamma = {a, B, ",", "fim de bloco"}.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::format::MAX_TAPES;
+use crate::pattern::{expand, ReadPattern, TransitionPattern, WritePattern};
+use crate::symbol::quoted;
+use crate::tape::DEFAULT_BLANK;
+use crate::template::{expand_templates, Template};
+use crate::{Move, TuringMachine};
+
+// Tipos de erro que podem ocorrer durante a análise da descrição
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedToken { expected: String, found: String },
+    MissingArrow { found: String },
+    MissingTupleElement { element: &'static str },
+    UnknownSection { found: String },
+    MissingSection { section: &'static str },
+    DuplicateSection { section: &'static str },
+    BlankInInputAlphabet { blank: String },
+    BlankNotInTapeAlphabet { blank: String },
+    TapeCountMismatch { expected: usize, found: usize },
+    EmptyTemplateValues { variable: String },
+    DuplicateTemplateVariable { variable: String },
+    NestedTemplate,
+}
+
+// Erro de análise junto com a posição em que foi encontrado
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedToken { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ParseErrorKind::MissingArrow { found } => {
+                write!(f, "expected '->' between the two sides of the transition, found {}", found)
+            }
+            ParseErrorKind::MissingTupleElement { element } => {
+                write!(f, "missing {} in transition tuple", element)
+            }
+            ParseErrorKind::UnknownSection { found } => {
+                write!(f, "unknown section starting with {}", found)
+            }
+            ParseErrorKind::MissingSection { section } => {
+                write!(f, "missing section '{}'", section)
+            }
+            ParseErrorKind::DuplicateSection { section } => {
+                write!(f, "section '{}' is defined more than once", section)
+            }
+            ParseErrorKind::BlankInInputAlphabet { blank } => {
+                write!(f, "the blank symbol '{}' cannot be part of the input alphabet", blank)
+            }
+            ParseErrorKind::BlankNotInTapeAlphabet { blank } => {
+                write!(f, "the blank symbol '{}' must be part of the tape alphabet", blank)
+            }
+            ParseErrorKind::TapeCountMismatch { expected, found } => write!(
+                f,
+                "transition reads {} tape(s), but the previous transitions read {}",
+                found, expected
+            ),
+            ParseErrorKind::EmptyTemplateValues { variable } => {
+                write!(f, "template variable '{}' has no values", variable)
+            }
+            ParseErrorKind::DuplicateTemplateVariable { variable } => {
+                write!(f, "template variable '{}' is declared more than once", variable)
+            }
+            ParseErrorKind::NestedTemplate => {
+                write!(f, "templates cannot be nested; declare all variables in the outer 'for'")
+            }
+        }
+    }
+}
+
+// Formata o erro no estilo de um compilador: arquivo:linha:coluna: error: mensagem
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}: error: {}", self.file, self.line, self.column, self.kind)
+    }
+}
+
+// Símbolos léxicos reconhecidos no arquivo de descrição
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    // Nome entre aspas, já sem as aspas e com os escapes resolvidos
+    Quoted(String),
+    // Aspas abertas e não fechadas até o fim da linha
+    UnterminatedQuote,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Comma,
+    Arrow,
+    Equals,
+    Colon,
+    Eof,
+}
+
+impl TokenKind {
+    // Descrição do símbolo léxico usada nas mensagens de erro
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Word(word) => format!("'{}'", word),
+            TokenKind::Quoted(name) => format!("'{}'", quoted(name)),
+            TokenKind::UnterminatedQuote => "an unterminated quoted name".to_string(),
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::LBrace => "'{'".to_string(),
+            TokenKind::RBrace => "'}'".to_string(),
+            TokenKind::Comma => "','".to_string(),
+            TokenKind::Arrow => "'->'".to_string(),
+            TokenKind::Equals => "'='".to_string(),
+            TokenKind::Colon => "':'".to_string(),
+            TokenKind::Eof => "end of file".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+// Verifica se a posição i da linha inicia um delimitador (ou espaço em branco)
+fn is_delimiter(chars: &[char], i: usize) -> bool {
+    match chars[i] {
+        '(' | ')' | '{' | '}' | ',' | '=' | ':' | '"' => true,
+        '-' => chars.get(i + 1) == Some(&'>'),
+        '/' => chars.get(i + 1) == Some(&'/'),
+        c => c.is_whitespace(),
+    }
+}
+
+// Função para escrever um nome da forma como ele deve aparecer na descrição: como está,
+// se ele for lido de volta como uma única palavra, ou entre aspas. O símbolo * também
+// vai entre aspas, para não ser lido como o curinga.
+pub fn quote_if_needed(name: &str) -> Cow<'_, str> {
+    let chars: Vec<char> = name.chars().collect();
+    if chars.is_empty() || name == "*" || (0..chars.len()).any(|i| is_delimiter(&chars, i)) {
+        Cow::Owned(quoted(name))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+// Comentário da descrição, com a linha em que aparece. O texto é o que segue o //.
+#[derive(Debug, Clone)]
+struct RawComment {
+    line: usize,
+    text: String,
+    // Se há símbolos léxicos antes do comentário na mesma linha
+    trailing: bool,
+}
+
+// Função auxiliar para dividir o texto da descrição em símbolos léxicos, separando os
+// comentários
+fn tokenize(source: &str) -> (Vec<Token>, Vec<RawComment>) {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut comments = Vec::new();
+    let mut last_line = (1, 1);
+
+    for (line_index, line) in source.lines().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let line_number = line_index + 1;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let column = i + 1;
+            let kind = match chars[i] {
+                c if c.is_whitespace() => {
+                    i += 1;
+                    continue;
+                }
+                // Comentário: ignora o restante da linha
+                '/' if chars.get(i + 1) == Some(&'/') => {
+                    let text: String = chars[i + 2..].iter().collect();
+                    let trailing = tokens.last().is_some_and(|token| token.line == line_number);
+                    comments.push(RawComment { line: line_number, text: text.trim_end().to_string(), trailing });
+                    break;
+                }
+                '(' => TokenKind::LParen,
+                ')' => TokenKind::RParen,
+                '{' => TokenKind::LBrace,
+                '}' => TokenKind::RBrace,
+                ',' => TokenKind::Comma,
+                '=' => TokenKind::Equals,
+                ':' => TokenKind::Colon,
+                '-' if chars.get(i + 1) == Some(&'>') => {
+                    i += 1;
+                    TokenKind::Arrow
+                }
+                // Nome entre aspas, que termina na mesma linha
+                '"' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    i += 1;
+                    while i < chars.len() {
+                        match chars[i] {
+                            '"' => {
+                                closed = true;
+                                break;
+                            }
+                            '\\' if matches!(chars.get(i + 1), Some('"') | Some('\\')) => {
+                                name.push(chars[i + 1]);
+                                i += 2;
+                            }
+                            c => {
+                                name.push(c);
+                                i += 1;
+                            }
+                        }
+                    }
+                    if closed {
+                        TokenKind::Quoted(name)
+                    } else {
+                        TokenKind::UnterminatedQuote
+                    }
+                }
+                _ => {
+                    let start = i;
+                    while i < chars.len() && !is_delimiter(&chars, i) {
+                        i += 1;
+                    }
+                    let word = chars[start..i].iter().collect();
+                    tokens.push(Token { kind: TokenKind::Word(word), line: line_number, column });
+                    continue;
+                }
+            };
+            i += 1;
+            tokens.push(Token { kind, line: line_number, column });
+        }
+
+        last_line = (line_number, chars.len() + 1);
+    }
+
+    tokens.push(Token { kind: TokenKind::Eof, line: last_line.0, column: last_line.1 });
+    (tokens, comments)
+}
+
+// Partes da sêxtupla, identificadas por rótulos no formato rotulado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Section {
+    States,
+    InputAlphabet,
+    TapeAlphabet,
+    Transitions,
+    InitialState,
+    FinalStates,
+    RejectStates,
+    Blank,
+    Tapes,
+}
+
+impl Section {
+    const ALL: [Section; 9] = [
+        Section::States,
+        Section::InputAlphabet,
+        Section::TapeAlphabet,
+        Section::Transitions,
+        Section::InitialState,
+        Section::FinalStates,
+        Section::RejectStates,
+        Section::Blank,
+        Section::Tapes,
+    ];
+
+    fn from_label(label: &str) -> Option<Section> {
+        match label {
+            "Q" => Some(Section::States),
+            "Sigma" | "Σ" => Some(Section::InputAlphabet),
+            "Gamma" | "Γ" => Some(Section::TapeAlphabet),
+            "delta" | "δ" => Some(Section::Transitions),
+            "q0" => Some(Section::InitialState),
+            "F" => Some(Section::FinalStates),
+            "R" | "reject" => Some(Section::RejectStates),
+            "blank" | "Blank" => Some(Section::Blank),
+            "tapes" => Some(Section::Tapes),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Section::States => "Q",
+            Section::InputAlphabet => "Sigma",
+            Section::TapeAlphabet => "Gamma",
+            Section::Transitions => "delta",
+            Section::InitialState => "q0",
+            Section::FinalStates => "F",
+            Section::RejectStates => "R",
+            Section::Blank => "blank",
+            Section::Tapes => "tapes",
+        }
+    }
+
+    // Os estados de rejeição, o símbolo branco e o número de fitas podem ser omitidos da
+    // descrição
+    fn is_required(self) -> bool {
+        !matches!(self, Section::RejectStates | Section::Blank | Section::Tapes)
+    }
+}
+
+// Valor lido para cada parte da sêxtupla no formato rotulado
+enum SectionValue {
+    Set(Vec<String>),
+    Transitions(Vec<TransitionPattern>),
+    State(String),
+    Symbol(String),
+    Count(usize),
+}
+
+// Elemento de uma tupla de transição: um nome, um conjunto de símbolos ou =. O nome
+// guarda se estava entre aspas, para distinguir o curinga * do símbolo "*".
+enum TupleElement {
+    Name { name: String, quoted: bool },
+    Set(Vec<String>),
+    Same,
+}
+
+// Analisador sintático descendente recursivo sobre a lista de símbolos léxicos
+struct Parser<'a> {
+    file: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+    errors: Vec<ParseError>,
+    // Número de fitas lido na primeira transição válida
+    tapes: Option<usize>,
+    // Transições como escritas na descrição, antes da expansão dos padrões
+    patterns: Vec<TransitionPattern>,
+    // Blocos for, com os intervalos das suas transições em `patterns`
+    templates: Vec<Template>,
+    // Estados gerados pelos blocos for e acrescentados a Q
+    generated_states: Vec<String>,
+    comments: Vec<RawComment>,
+    // Linha em que começa cada transição lida, cada bloco for e cada rótulo de seção,
+    // usadas para associar os comentários às partes da descrição
+    transition_lines: Vec<usize>,
+    template_lines: Vec<usize>,
+    section_lines: Vec<(Section, usize)>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &str, file: &'a str) -> Self {
+        let (tokens, comments) = tokenize(source);
+        Parser {
+            file,
+            tokens,
+            position: 0,
+            errors: Vec::new(),
+            tapes: None,
+            patterns: Vec::new(),
+            templates: Vec::new(),
+            generated_states: Vec::new(),
+            comments,
+            transition_lines: Vec::new(),
+            template_lines: Vec::new(),
+            section_lines: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    // Verifica se o símbolo léxico atual inicia uma seção rotulada (rótulo seguido de
+    // '=' ou ':')
+    fn at_section_label(&self) -> bool {
+        matches!(self.peek().kind, TokenKind::Word(_))
+            && matches!(
+                self.tokens.get(self.position + 1).map(|token| &token.kind),
+                Some(TokenKind::Equals) | Some(TokenKind::Colon)
+            )
+    }
+
+    // Avança para o próximo símbolo léxico, sem nunca passar do fim do arquivo
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error_at(&self, token: &Token, kind: ParseErrorKind) -> ParseError {
+        ParseError { file: self.file.to_string(), line: token.line, column: token.column, kind }
+    }
+
+    fn unexpected(&self, token: &Token, expected: &str) -> ParseError {
+        self.error_at(
+            token,
+            ParseErrorKind::UnexpectedToken {
+                expected: expected.to_string(),
+                found: token.kind.describe(),
+            },
+        )
+    }
+
+    // Consome o símbolo léxico se ele for do tipo esperado
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if &self.peek().kind == kind {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<Token, ParseError> {
+        if self.peek().kind == kind {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(self.peek(), &kind.describe()))
+        }
+    }
+
+    // Nome de um estado ou símbolo no símbolo léxico atual: uma palavra ou um texto
+    // entre aspas não vazio
+    fn peek_name(&self) -> Option<String> {
+        match &self.peek().kind {
+            TokenKind::Word(word) => Some(word.clone()),
+            TokenKind::Quoted(name) if !name.is_empty() => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    fn expect_word(&mut self, expected: &str) -> Result<(String, Token), ParseError> {
+        match self.peek_name() {
+            Some(name) => Ok((name, self.advance())),
+            None => Err(self.unexpected(self.peek(), expected)),
+        }
+    }
+
+    // Descarta os símbolos léxicos restantes da linha atual, para continuar a análise
+    // a partir da próxima linha depois de um erro
+    fn skip_line(&mut self, line: usize) {
+        while self.peek().kind != TokenKind::Eof && self.peek().line == line {
+            self.advance();
+        }
+    }
+
+    // Registra o erro e sincroniza a análise na linha seguinte àquela em que a
+    // construção com erro começou
+    fn recover(&mut self, error: ParseError, start_line: usize) {
+        self.errors.push(error);
+        self.skip_line(start_line);
+    }
+
+    // Lê um conjunto no formato {a,b,c}
+    fn parse_set(&mut self, what: &str) -> Result<Vec<String>, ParseError> {
+        let open = self.peek().clone();
+        if open.kind != TokenKind::LBrace {
+            return Err(self.unexpected(&open, &format!("'{{' opening {}", what)));
+        }
+        self.advance();
+
+        let mut elements = Vec::new();
+        loop {
+            if self.eat(&TokenKind::RBrace) {
+                return Ok(elements);
+            }
+            let Some(name) = self.peek_name() else {
+                return Err(self.unexpected(self.peek(), &format!("an element of {}", what)));
+            };
+            self.advance();
+            elements.push(name);
+            if !self.eat(&TokenKind::Comma) && self.peek().kind != TokenKind::RBrace {
+                return Err(self.unexpected(self.peek(), "',' or '}'"));
+            }
+        }
+    }
+
+    // Lê uma tupla entre parênteses com um ou mais elementos, devolvendo-os, cada um com
+    // o seu primeiro símbolo léxico, junto com o ')' que fecha a tupla. `element` dá o
+    // nome do elemento esperado em cada posição.
+    fn parse_tuple(
+        &mut self,
+        element: impl Fn(usize) -> &'static str,
+    ) -> Result<(Vec<(TupleElement, Token)>, Token), ParseError> {
+        self.expect(TokenKind::LParen)?;
+
+        let mut values = Vec::new();
+        loop {
+            let element = element(values.len());
+            if matches!(self.peek().kind, TokenKind::RParen | TokenKind::Comma) {
+                let token = self.peek().clone();
+                return Err(self.error_at(&token, ParseErrorKind::MissingTupleElement { element }));
+            }
+            let token = self.peek().clone();
+            let value = if token.kind == TokenKind::LBrace {
+                TupleElement::Set(self.parse_set("the set of read symbols")?)
+            } else if self.eat(&TokenKind::Equals) {
+                TupleElement::Same
+            } else {
+                let quoted = matches!(token.kind, TokenKind::Quoted(_));
+                let (name, _) = self.expect_word(element)?;
+                TupleElement::Name { name, quoted }
+            };
+            values.push((value, token));
+
+            match self.peek().kind {
+                TokenKind::Comma => {
+                    self.advance();
+                }
+                TokenKind::RParen => return Ok((values, self.advance())),
+                _ => return Err(self.unexpected(self.peek(), "',' or ')'")),
+            }
+        }
+    }
+
+    // Converte um elemento da tupla em um estado
+    fn tuple_state(&self, (element, token): &(TupleElement, Token), what: &str) -> Result<String, ParseError> {
+        match element {
+            TupleElement::Name { name, .. } => Ok(name.clone()),
+            _ => Err(self.unexpected(token, what)),
+        }
+    }
+
+    // Converte um elemento da tupla em um símbolo lido, um conjunto de símbolos ou o
+    // curinga
+    fn tuple_read(&self, (element, token): &(TupleElement, Token)) -> Result<ReadPattern, ParseError> {
+        match element {
+            TupleElement::Name { name, quoted: false } if name == "*" => Ok(ReadPattern::Any),
+            TupleElement::Name { name, .. } => Ok(ReadPattern::Symbol(name.clone())),
+            TupleElement::Set(symbols) if symbols.is_empty() => {
+                Err(self.unexpected(token, "a non-empty set of read symbols"))
+            }
+            TupleElement::Set(symbols) => Ok(ReadPattern::Set(symbols.clone())),
+            TupleElement::Same => Err(self.unexpected(token, "a read symbol, a set of symbols or '*'")),
+        }
+    }
+
+    // Converte um elemento da tupla em um símbolo escrito ou em =
+    fn tuple_write(&self, (element, token): &(TupleElement, Token)) -> Result<WritePattern, ParseError> {
+        match element {
+            TupleElement::Name { name, quoted: false } if name == "*" => {
+                Err(self.unexpected(token, "a write symbol or '='"))
+            }
+            TupleElement::Name { name, .. } => Ok(WritePattern::Symbol(name.clone())),
+            TupleElement::Same => Ok(WritePattern::Same),
+            TupleElement::Set(_) => Err(self.unexpected(token, "a write symbol or '='")),
+        }
+    }
+
+    // Converte um elemento da tupla em um movimento da cabeça de leitura/escrita
+    fn tuple_move(&self, (element, token): &(TupleElement, Token)) -> Result<Move, ParseError> {
+        let direction = match element {
+            TupleElement::Name { name, .. } => Move::from_symbol(name),
+            _ => None,
+        };
+        direction.ok_or_else(|| self.unexpected(token, "a move direction (L, R, S, E, D or N)"))
+    }
+
+    // Lê uma transição no formato (qi, a) -> (qj, b, M) ou, com k fitas,
+    // (qi, a1, ..., ak) -> (qj, b1, ..., bk, M1, ..., Mk)
+    fn parse_transition(&mut self) -> Result<TransitionPattern, ParseError> {
+        let (from, close) = self.parse_tuple(|i| if i == 0 { "state" } else { "read symbol" })?;
+        if from.len() < 2 {
+            return Err(self.error_at(&close, ParseErrorKind::MissingTupleElement { element: "read symbol" }));
+        }
+        let tapes = from.len() - 1;
+
+        if self.peek().kind != TokenKind::Arrow {
+            let token = self.peek().clone();
+            let found = token.kind.describe();
+            return Err(self.error_at(&token, ParseErrorKind::MissingArrow { found }));
+        }
+        self.advance();
+
+        let element = |i| match i {
+            0 => "next state",
+            i if i <= tapes => "write symbol",
+            _ => "move direction",
+        };
+        let (to, close) = self.parse_tuple(element)?;
+        if to.len() < 1 + 2 * tapes {
+            let element = element(to.len());
+            return Err(self.error_at(&close, ParseErrorKind::MissingTupleElement { element }));
+        }
+        if let Some((_, token)) = to.get(1 + 2 * tapes) {
+            return Err(self.unexpected(token, "')' closing the transition tuple"));
+        }
+
+        Ok(TransitionPattern {
+            from_state: self.tuple_state(&from[0], "state")?,
+            read: from[1..].iter().map(|element| self.tuple_read(element)).collect::<Result<_, _>>()?,
+            to_state: self.tuple_state(&to[0], "next state")?,
+            write: to[1..=tapes].iter().map(|element| self.tuple_write(element)).collect::<Result<_, _>>()?,
+            moves: to[1 + tapes..].iter().map(|element| self.tuple_move(element)).collect::<Result<_, _>>()?,
+        })
+    }
+
+    // Lê uma transição e a acrescenta à lista; em caso de erro, registra o erro e
+    // continua a partir da linha seguinte
+    fn parse_transition_into(&mut self, transitions: &mut Vec<TransitionPattern>) {
+        let start = self.peek().clone();
+        match self.parse_transition() {
+            Ok(transition) => {
+                // Todas as transições devem ler o mesmo número de fitas que a primeira
+                let expected = *self.tapes.get_or_insert(transition.tapes());
+                if transition.tapes() == expected {
+                    transitions.push(transition);
+                    self.transition_lines.push(start.line);
+                } else {
+                    let found = transition.tapes();
+                    let error = self.error_at(&start, ParseErrorKind::TapeCountMismatch { expected, found });
+                    self.errors.push(error);
+                }
+                self.eat(&TokenKind::Comma);
+            }
+            Err(error) => self.recover(error, start.line),
+        }
+    }
+
+    // Verifica se o símbolo léxico atual inicia um bloco for
+    fn at_template(&self) -> bool {
+        self.peek().kind == TokenKind::Word("for".to_string()) && !self.at_section_label()
+    }
+
+    // Lê as variáveis de um bloco for no formato x in {a, b}, y in {c, d}. Uma variável
+    // repetida ou sem valores é registrada como erro sem interromper a leitura do bloco.
+    fn parse_bindings(&mut self) -> Result<Vec<(String, Vec<String>)>, ParseError> {
+        let mut bindings: Vec<(String, Vec<String>)> = Vec::new();
+        loop {
+            let (variable, token) = self.expect_word("a template variable")?;
+            if self.peek().kind != TokenKind::Word("in".to_string()) {
+                return Err(self.unexpected(self.peek(), "'in'"));
+            }
+            self.advance();
+            let values = self.parse_set("the values of the template variable")?;
+            if bindings.iter().any(|(other, _)| *other == variable) {
+                self.errors.push(self.error_at(&token, ParseErrorKind::DuplicateTemplateVariable { variable }));
+            } else if values.is_empty() {
+                self.errors.push(self.error_at(&token, ParseErrorKind::EmptyTemplateValues { variable }));
+            } else {
+                bindings.push((variable, values));
+            }
+            if !self.eat(&TokenKind::Comma) {
+                return Ok(bindings);
+            }
+        }
+    }
+
+    // Lê um bloco for x in {a, b} { (..) -> (..) ... }, acrescentando as transições do
+    // corpo, como escritas, à lista. Um bloco dentro de outro é registrado como erro, mas
+    // lido até o fim, para que as suas chaves não gerem mais erros.
+    fn parse_template(&mut self, transitions: &mut Vec<TransitionPattern>, nested: bool) {
+        let start = self.advance();
+        if nested {
+            self.errors.push(self.error_at(&start, ParseErrorKind::NestedTemplate));
+        }
+        let bindings = match self.parse_bindings() {
+            Ok(bindings) => bindings,
+            Err(error) => return self.recover(error, start.line),
+        };
+        if self.peek().kind != TokenKind::LBrace {
+            let error = self.unexpected(self.peek(), "'{' opening the template body");
+            return self.recover(error, start.line);
+        }
+        self.advance();
+
+        let first = transitions.len();
+        loop {
+            match self.peek().kind {
+                TokenKind::RBrace => {
+                    self.advance();
+                    break;
+                }
+                TokenKind::Eof => {
+                    self.errors.push(self.unexpected(self.peek(), "'}' closing the template body"));
+                    break;
+                }
+                _ if self.at_template() => self.parse_template(transitions, true),
+                _ => self.parse_transition_into(transitions),
+            }
+        }
+        if !nested {
+            self.templates.push(Template { bindings, transitions: first..transitions.len() });
+            self.template_lines.push(start.line);
+        }
+        self.eat(&TokenKind::Comma);
+    }
+
+    // Lê uma transição ou um bloco for
+    fn parse_delta_item(&mut self, transitions: &mut Vec<TransitionPattern>) {
+        if self.at_template() {
+            self.parse_template(transitions, false);
+        } else {
+            self.parse_transition_into(transitions);
+        }
+    }
+
+    // Lê o bloco de transições {(..) -> (..), ...}, continuando após transições inválidas
+    fn parse_transitions(&mut self) -> Result<Vec<TransitionPattern>, ParseError> {
+        let open = self.peek().clone();
+        if open.kind != TokenKind::LBrace {
+            return Err(self.unexpected(&open, "'{' opening the transition function"));
+        }
+        self.advance();
+
+        let mut transitions = Vec::new();
+        loop {
+            match self.peek().kind {
+                TokenKind::RBrace => {
+                    self.advance();
+                    return Ok(transitions);
+                }
+                TokenKind::Eof => return Err(self.unexpected(self.peek(), "'}' closing the transition function")),
+                _ => self.parse_delta_item(&mut transitions),
+            }
+        }
+    }
+
+    // Lê as transições da seção delta do formato rotulado, que vão até o próximo
+    // rótulo ou até o fim do arquivo
+    fn parse_transition_list(&mut self) -> Vec<TransitionPattern> {
+        let mut transitions = Vec::new();
+        while self.peek().kind != TokenKind::Eof && !self.at_section_label() {
+            self.parse_delta_item(&mut transitions);
+        }
+        transitions
+    }
+
+    // Lê a máquina em qualquer um dos dois formatos aceitos
+    fn parse_machine(&mut self) -> TuringMachine {
+        if self.at_section_label() {
+            self.parse_labeled()
+        } else {
+            self.parse_positional()
+        }
+    }
+
+    // Registra o erro e descarta os símbolos léxicos até a próxima seção rotulada
+    fn recover_section(&mut self, error: ParseError) {
+        self.errors.push(error);
+        while self.peek().kind != TokenKind::Eof && !self.at_section_label() {
+            self.advance();
+        }
+    }
+
+    // Lê o valor de uma seção rotulada, logo após o '=' ou ':'
+    fn parse_section_value(&mut self, section: Section) -> Result<SectionValue, ParseError> {
+        match section {
+            Section::States => self.parse_set("the set of states").map(SectionValue::Set),
+            Section::InputAlphabet => self.parse_set("the input alphabet").map(SectionValue::Set),
+            Section::TapeAlphabet => self.parse_set("the tape alphabet").map(SectionValue::Set),
+            Section::FinalStates => self.parse_set("the set of final states").map(SectionValue::Set),
+            Section::RejectStates => self.parse_set("the set of reject states").map(SectionValue::Set),
+            Section::InitialState => self
+                .expect_word("the initial state")
+                .map(|(state, _)| SectionValue::State(state)),
+            Section::Transitions if self.peek().kind == TokenKind::LBrace => {
+                self.parse_transitions().map(SectionValue::Transitions)
+            }
+            Section::Transitions => Ok(SectionValue::Transitions(self.parse_transition_list())),
+            Section::Blank => self
+                .expect_word("the blank symbol")
+                .map(|(symbol, _)| SectionValue::Symbol(symbol)),
+            Section::Tapes => self.parse_tape_count().map(SectionValue::Count),
+        }
+    }
+
+    // Lê o número de fitas, que deve concordar com o das transições já lidas; as
+    // transições seguintes são comparadas com ele
+    fn parse_tape_count(&mut self) -> Result<usize, ParseError> {
+        let expected = format!("a number of tapes between 1 and {}", MAX_TAPES);
+        let token = self.peek().clone();
+        let tapes = match &token.kind {
+            TokenKind::Word(word) => word.parse().ok().filter(|tapes| (1..=MAX_TAPES).contains(tapes)),
+            _ => None,
+        };
+        let Some(tapes) = tapes else {
+            return Err(self.unexpected(&token, &expected));
+        };
+        self.advance();
+        match self.tapes {
+            Some(found) if found != tapes => {
+                let error = self.error_at(&token, ParseErrorKind::TapeCountMismatch { expected: tapes, found });
+                self.errors.push(error);
+            }
+            _ => self.tapes = Some(tapes),
+        }
+        Ok(tapes)
+    }
+
+    // Verifica se o símbolo branco pertence a Γ e não pertence a Σ. Os símbolos léxicos
+    // indicam onde cada alfabeto foi declarado; se um deles não pôde ser lido, a
+    // verificação correspondente é omitida para não repetir erros.
+    fn check_blank(&mut self, tm: &TuringMachine, input_alphabet: Option<Token>, tape_alphabet: Option<Token>) {
+        let blank = &tm.blank;
+        if let Some(token) = input_alphabet {
+            if tm.alphabet.contains(blank) {
+                let error = self.error_at(&token, ParseErrorKind::BlankInInputAlphabet { blank: blank.clone() });
+                self.errors.push(error);
+            }
+        }
+        if let Some(token) = tape_alphabet {
+            if !tm.tape_alphabet.contains(blank) {
+                let error = self.error_at(&token, ParseErrorKind::BlankNotInTapeAlphabet { blank: blank.clone() });
+                self.errors.push(error);
+            }
+        }
+    }
+
+    // Expande os blocos for e depois os padrões nas transições da máquina, acrescentando
+    // a Q os estados gerados pelos blocos que ainda não estão lá
+    fn expand_transitions(&mut self, tm: &mut TuringMachine) {
+        let (patterns, generated) = expand_templates(&self.patterns, &self.templates);
+        tm.transitions = expand(&patterns, &tm.tape_alphabet);
+        self.generated_states = generated.into_iter().filter(|state| !tm.states.contains(state)).collect();
+        tm.states.extend(self.generated_states.iter().cloned());
+    }
+
+    // Lê a sêxtupla no formato rotulado, em que as seções podem aparecer em qualquer ordem
+    fn parse_labeled(&mut self) -> TuringMachine {
+        let mut values: Vec<Option<SectionValue>> = Section::ALL.iter().map(|_| None).collect();
+        let mut seen = [false; Section::ALL.len()];
+        let mut labels: Vec<Option<Token>> = Section::ALL.iter().map(|_| None).collect();
+
+        while self.peek().kind != TokenKind::Eof {
+            let token = self.peek().clone();
+            let section = match &token.kind {
+                TokenKind::Word(label) if self.at_section_label() => Section::from_label(label),
+                _ => {
+                    let error = self.unexpected(&token, "a section label such as 'Q' or 'delta'");
+                    self.recover_section(error);
+                    continue;
+                }
+            };
+            let Some(section) = section else {
+                let found = token.kind.describe();
+                let error = self.error_at(&token, ParseErrorKind::UnknownSection { found });
+                self.advance();
+                self.recover_section(error);
+                continue;
+            };
+
+            // Consome o rótulo e o '=' ou ':' que o segue
+            self.advance();
+            self.advance();
+            self.section_lines.push((section, token.line));
+
+            let index = section as usize;
+            if seen[index] {
+                let error = self.error_at(&token, ParseErrorKind::DuplicateSection { section: section.label() });
+                self.errors.push(error);
+            }
+            seen[index] = true;
+
+            match self.parse_section_value(section) {
+                Ok(value) => {
+                    values[index] = Some(value);
+                    labels[index] = Some(token);
+                    self.eat(&TokenKind::Comma);
+                }
+                Err(error) => self.recover_section(error),
+            }
+        }
+
+        let eof = self.peek().clone();
+        for section in Section::ALL {
+            if section.is_required() && !seen[section as usize] {
+                let error = self.error_at(&eof, ParseErrorKind::MissingSection { section: section.label() });
+                self.errors.push(error);
+            }
+        }
+
+        let mut tm = TuringMachine {
+            states: Vec::new(),
+            alphabet: Vec::new(),
+            tape_alphabet: Vec::new(),
+            transitions: Vec::new(),
+            initial_state: String::new(),
+            accept_states: Vec::new(),
+            reject_states: Vec::new(),
+            blank: DEFAULT_BLANK.to_string(),
+            tapes: self.tapes.unwrap_or(1),
+        };
+        for (section, value) in Section::ALL.into_iter().zip(values) {
+            match (section, value) {
+                (Section::States, Some(SectionValue::Set(set))) => tm.states = set,
+                (Section::InputAlphabet, Some(SectionValue::Set(set))) => tm.alphabet = set,
+                (Section::TapeAlphabet, Some(SectionValue::Set(set))) => tm.tape_alphabet = set,
+                (Section::FinalStates, Some(SectionValue::Set(set))) => tm.accept_states = set,
+                (Section::RejectStates, Some(SectionValue::Set(set))) => tm.reject_states = set,
+                (_, Some(SectionValue::Transitions(patterns))) => self.patterns = patterns,
+                (_, Some(SectionValue::State(state))) => tm.initial_state = state,
+                (_, Some(SectionValue::Symbol(blank))) => tm.blank = blank,
+                (_, Some(SectionValue::Count(tapes))) => tm.tapes = tapes,
+                _ => {}
+            }
+        }
+        self.expand_transitions(&mut tm);
+
+        let input_alphabet = labels[Section::InputAlphabet as usize].take();
+        let tape_alphabet = labels[Section::TapeAlphabet as usize].take();
+        self.check_blank(&tm, input_alphabet, tape_alphabet);
+        tm
+    }
+
+    // Lê a sêxtupla (Q, Σ, Γ, δ, q0, F) no formato posicional, uma parte por vez
+    fn parse_positional(&mut self) -> TuringMachine {
+        self.eat(&TokenKind::LParen);
+
+        let mut sets = Vec::new();
+        let mut set_tokens = Vec::new();
+        for what in ["the set of states", "the input alphabet", "the tape alphabet"] {
+            let start = self.peek().clone();
+            let start_line = start.line;
+            match self.parse_set(what) {
+                Ok(set) => {
+                    sets.push(set);
+                    set_tokens.push(Some(start));
+                }
+                Err(error) => {
+                    self.recover(error, start_line);
+                    sets.push(Vec::new());
+                    set_tokens.push(None);
+                }
+            }
+            self.eat(&TokenKind::Comma);
+        }
+
+        let start_line = self.peek().line;
+        self.patterns = self.parse_transitions().unwrap_or_else(|error| {
+            self.recover(error, start_line);
+            Vec::new()
+        });
+        self.eat(&TokenKind::Comma);
+
+        let start_line = self.peek().line;
+        let initial_state = match self.expect_word("the initial state") {
+            Ok((state, _)) => state,
+            Err(error) => {
+                self.recover(error, start_line);
+                String::new()
+            }
+        };
+        self.eat(&TokenKind::Comma);
+
+        let start_line = self.peek().line;
+        let accept_states = self.parse_set("the set of final states").unwrap_or_else(|error| {
+            self.recover(error, start_line);
+            Vec::new()
+        });
+        self.eat(&TokenKind::Comma);
+
+        // Conjunto opcional de estados de rejeição, depois dos estados finais
+        let mut reject_states = Vec::new();
+        if self.peek().kind == TokenKind::LBrace {
+            let start_line = self.peek().line;
+            match self.parse_set("the set of reject states") {
+                Ok(set) => reject_states = set,
+                Err(error) => self.recover(error, start_line),
+            }
+            self.eat(&TokenKind::Comma);
+        }
+
+        // Símbolo branco opcional, no fim da descrição
+        let mut blank = DEFAULT_BLANK.to_string();
+        if let Some(symbol) = self.peek_name() {
+            self.advance();
+            blank = symbol;
+            self.eat(&TokenKind::Comma);
+        }
+        self.eat(&TokenKind::RParen);
+
+        if self.peek().kind != TokenKind::Eof {
+            let token = self.peek().clone();
+            let found = token.kind.describe();
+            self.errors.push(self.error_at(&token, ParseErrorKind::UnknownSection { found }));
+        }
+
+        let mut sets = sets.into_iter();
+        let (states, alphabet, tape_alphabet) =
+            (sets.next().unwrap_or_default(), sets.next().unwrap_or_default(), sets.next().unwrap_or_default());
+        let mut tm = TuringMachine {
+            transitions: Vec::new(),
+            states,
+            alphabet,
+            tape_alphabet,
+            initial_state,
+            accept_states,
+            reject_states,
+            blank,
+            tapes: self.tapes.unwrap_or(1),
+        };
+        self.expand_transitions(&mut tm);
+
+        let mut set_tokens = set_tokens.into_iter().skip(1);
+        let input_alphabet = set_tokens.next().flatten();
+        let tape_alphabet = set_tokens.next().flatten();
+        self.check_blank(&tm, input_alphabet, tape_alphabet);
+        tm
+    }
+}
+
+// Erro ao ler uma descrição a partir de um leitor qualquer
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(Vec<ParseError>),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "unable to read the description: {}", error),
+            ReadError::Parse(errors) => {
+                let messages: Vec<String> = errors.iter().map(ParseError::to_string).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+        }
+    }
+}
+
+// Lê e analisa uma descrição de máquina de Turing a partir de um leitor qualquer
+pub fn parse_reader(mut reader: impl Read, file: &str) -> Result<TuringMachine, ReadError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source).map_err(ReadError::Io)?;
+    parse_description(&source, file).map_err(ReadError::Parse)
+}
+
+// Analisa o texto de uma descrição de máquina de Turing. O nome do arquivo é usado
+// apenas para compor as mensagens de erro.
+pub fn parse_description(source: &str, file: &str) -> Result<TuringMachine, Vec<ParseError>> {
+    parse_with_comments(source, file).map(|description| description.machine)
+}
+
+// Parte da descrição à qual um comentário pertence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CommentAnchor {
+    // Comentários antes de qualquer outro conteúdo
+    Header,
+    // Índice da transição, como escrita na descrição, em `Description::transitions`
+    Transition(usize),
+    // Índice do bloco for em `Description::templates`
+    Template(usize),
+    Section(Section),
+    // Comentários que não pertencem a nenhuma outra parte, como os do fim do arquivo
+    End,
+}
+
+// Comentário da descrição associado a uma parte dela. Um comentário no fim de uma linha
+// (trailing) acompanha a parte escrita nessa linha; os demais, a parte seguinte.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Comment {
+    pub anchor: CommentAnchor,
+    pub text: String,
+    pub trailing: bool,
+}
+
+impl Parser<'_> {
+    // Associa cada comentário à transição ou à seção que começa na sua linha (se ele
+    // estiver no fim da linha) ou na linha do próximo símbolo léxico. Os demais ficam
+    // com a seção em que aparecem ou, no formato posicional, com o fim do arquivo.
+    fn comment_anchors(&self) -> Vec<Comment> {
+        let first_line = self.tokens.first().filter(|token| token.kind != TokenKind::Eof).map(|token| token.line);
+        let item_at = |line: usize| {
+            if let Some(i) = self.template_lines.iter().position(|&start| start == line) {
+                Some(CommentAnchor::Template(i))
+            } else if let Some(i) = self.transition_lines.iter().position(|&start| start == line) {
+                Some(CommentAnchor::Transition(i))
+            } else {
+                self.section_lines.iter().find(|(_, start)| *start == line).map(|&(section, _)| CommentAnchor::Section(section))
+            }
+        };
+        let enclosing = |line: usize| {
+            self.section_lines
+                .iter()
+                .rev()
+                .find(|(_, start)| *start <= line)
+                .map_or(CommentAnchor::End, |&(section, _)| CommentAnchor::Section(section))
+        };
+
+        self.comments
+            .iter()
+            .map(|comment| {
+                let trailing_anchor = if comment.trailing { item_at(comment.line) } else { None };
+                let anchor = trailing_anchor.unwrap_or_else(|| {
+                    if first_line.is_none_or(|first_line| comment.line < first_line) {
+                        return CommentAnchor::Header;
+                    }
+                    let next = self.tokens.iter().find(|token| token.line > comment.line && token.kind != TokenKind::Eof);
+                    match next {
+                        Some(token) => item_at(token.line).unwrap_or_else(|| enclosing(comment.line)),
+                        None => CommentAnchor::End,
+                    }
+                });
+                Comment { anchor, text: comment.text.clone(), trailing: trailing_anchor.is_some() }
+            })
+            .collect()
+    }
+}
+
+// Descrição analisada: a máquina, com os blocos for e os padrões de transição já
+// expandidos, as transições e os blocos for como escritos, os estados que os blocos
+// acrescentaram a Q e os comentários
+pub(crate) struct Description {
+    pub machine: TuringMachine,
+    pub transitions: Vec<TransitionPattern>,
+    pub templates: Vec<Template>,
+    pub generated_states: Vec<String>,
+    pub comments: Vec<Comment>,
+}
+
+// Analisa a descrição como `parse_description`, devolvendo também as transições como
+// escritas e os comentários, cada um associado à parte da descrição a que pertence
+pub(crate) fn parse_with_comments(source: &str, file: &str) -> Result<Description, Vec<ParseError>> {
+    let mut parser = Parser::new(source, file);
+    let machine = parser.parse_machine();
+
+    if parser.errors.is_empty() {
+        let comments = parser.comment_anchors();
+        Ok(Description {
+            machine,
+            transitions: parser.patterns,
+            templates: parser.templates,
+            generated_states: parser.generated_states,
+            comments,
+        })
+    } else {
+        Err(parser.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Q = {q0}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {}\ndelta:\n";
+
+    // Linha, coluna e tipo de cada erro da descrição
+    fn errors(source: &str) -> Vec<(usize, usize, ParseErrorKind)> {
+        let errors = parse_description(source, "test.txt").unwrap_err();
+        errors.into_iter().map(|error| (error.line, error.column, error.kind)).collect()
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        assert_eq!(
+            errors(&format!("{}(q0, a) (q0, a, R)\n", HEADER)),
+            vec![(7, 9, ParseErrorKind::MissingArrow { found: "'('".to_string() })]
+        );
+        assert_eq!(
+            errors(&format!("{}(q0, a) -> (q0, a)\n", HEADER)),
+            vec![(7, 18, ParseErrorKind::MissingTupleElement { element: "move direction" })]
+        );
+        assert_eq!(
+            errors(&format!("{}(q0, a) -> (q0, a, R)\n(q0, a, a) -> (q0, a, a, R, R)\n", HEADER)),
+            vec![(8, 1, ParseErrorKind::TapeCountMismatch { expected: 1, found: 2 })]
+        );
+        assert_eq!(
+            errors("Q = {q0}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {}\nfoo = 3\ndelta:\n"),
+            vec![(6, 1, ParseErrorKind::UnknownSection { found: "'foo'".to_string() })]
+        );
+    }
+
+    #[test]
+    fn tape_count_must_agree_with_the_transitions() {
+        let source = "Q = {q0}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {}\ntapes = 2\ndelta:\n";
+        assert_eq!(parse_description(source, "test.txt").unwrap().tapes, 2);
+        assert_eq!(
+            errors(&format!("{}(q0, a) -> (q0, a, R)\n", source)),
+            vec![(8, 1, ParseErrorKind::TapeCountMismatch { expected: 2, found: 1 })]
+        );
+        assert_eq!(
+            errors(&format!("{}(q0, a) -> (q0, a, R)\ntapes = 2\n", HEADER)),
+            vec![(8, 9, ParseErrorKind::TapeCountMismatch { expected: 2, found: 1 })]
+        );
+        let expected = "a number of tapes between 1 and 256".to_string();
+        assert_eq!(
+            errors(&format!("{}tapes = 0\n", HEADER)),
+            vec![(7, 9, ParseErrorKind::UnexpectedToken { expected, found: "'0'".to_string() })]
+        );
+    }
+
+    #[test]
+    fn section_errors_point_at_the_section() {
+        assert_eq!(
+            errors("Q = {q0}\nQ = {q0}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {}\ndelta:\n"),
+            vec![(2, 1, ParseErrorKind::DuplicateSection { section: "Q" })]
+        );
+        assert_eq!(
+            errors("Q = {q0}\nSigma = {a, B}\nGamma = {a, B}\nq0 = q0\nF = {}\ndelta:\n"),
+            vec![(2, 1, ParseErrorKind::BlankInInputAlphabet { blank: "B".to_string() })]
+        );
+        assert_eq!(
+            errors("Q = {q0}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\ndelta:\n"),
+            vec![(5, 7, ParseErrorKind::MissingSection { section: "F" })]
+        );
+    }
+
+    #[test]
+    fn analysis_continues_after_an_invalid_transition() {
+        let source = format!("{}(q0, a) (q0, a, R)\n(q0, B) -> (q0, B)\n(q0, a) -> (q0, a, R)\n", HEADER);
+        let lines: Vec<usize> = errors(&source).into_iter().map(|(line, _, _)| line).collect();
+        assert_eq!(lines, vec![7, 8]);
+    }
+
+    #[test]
+    fn quoted_names_are_unescaped() {
+        let source = "Q = {q0, \"q\\\"1\", \"a\\\\b\", \"q fim\"}\nSigma = {a}\nGamma = {a, \",\", B}\nq0 = q0\n\
+                      F = {\"q fim\"}\ndelta:\n(q0, \",\") -> (\"a\\\\b\", a, R) // comentário\n";
+        let tm = parse_description(source, "test.txt").unwrap();
+        assert_eq!(tm.states, ["q0", "q\"1", "a\\b", "q fim"]);
+        assert_eq!(tm.tape_alphabet, ["a", ",", "B"]);
+        assert_eq!(tm.transitions[0].to_state, "a\\b");
+    }
+
+    #[test]
+    fn names_are_quoted_only_when_needed() {
+        for name in ["a", "q_inicio", "#a", "X1", "é"] {
+            assert_eq!(quote_if_needed(name), name);
+        }
+        let quoted = [("q fim", "\"q fim\""), ("*", "\"*\""), (",", "\",\""), ("a\"b", "\"a\\\"b\""), ("a//b", "\"a//b\"")];
+        for (name, quoted) in quoted {
+            assert_eq!(quote_if_needed(name), quoted);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// Transições como escritas na descrição, antes da expansão. Além de um símbolo, cada
+// posição lida pode ser um conjunto de símbolos, como {a, b}, ou o curinga *, que vale
+// para qualquer símbolo de Γ; cada posição escrita pode ser =, que escreve de volta o
+// símbolo lido na mesma fita:
+//
+//     (q0, {a, b}) -> (q0, =, R)
+//     (q0, *) -> (q1, =, L)
+//
+// Ao carregar a descrição, cada padrão é expandido em transições comuns, uma para cada
+// combinação de símbolos lidos, de modo que a execução não conhece os padrões. Uma
+// combinação gerada por um padrão menos específico (com mais curingas ou, empatado
+// nisso, com mais conjuntos) é descartada quando um padrão mais específico gera a
+// mesma combinação; assim, o curinga vale apenas para os símbolos sem outra transição.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::parser::quote_if_needed;
+use crate::{Move, Transition};
+
+// Símbolo lido em uma fita
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReadPattern {
+    Symbol(String),
+    Set(Vec<String>),
+    Any,
+}
+
+// Símbolo escrito em uma fita
+#[derive(Debug, Clone, PartialEq)]
+pub enum WritePattern {
+    Symbol(String),
+    // Escreve o símbolo lido na mesma fita
+    Same,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionPattern {
+    pub from_state: String,
+    pub read: Vec<ReadPattern>,
+    pub to_state: String,
+    pub write: Vec<WritePattern>,
+    pub moves: Vec<Move>,
+}
+
+impl fmt::Display for ReadPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadPattern::Symbol(symbol) => write!(f, "{}", quote_if_needed(symbol)),
+            ReadPattern::Set(symbols) => {
+                let symbols: Vec<_> = symbols.iter().map(|symbol| quote_if_needed(symbol)).collect();
+                write!(f, "{{{}}}", symbols.join(", "))
+            }
+            ReadPattern::Any => write!(f, "*"),
+        }
+    }
+}
+
+impl fmt::Display for WritePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WritePattern::Symbol(symbol) => write!(f, "{}", quote_if_needed(symbol)),
+            WritePattern::Same => write!(f, "="),
+        }
+    }
+}
+
+// Mesmo formato da descrição, como em (q0, {a, b}) -> (q0, =, R)
+impl fmt::Display for TransitionPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}", quote_if_needed(&self.from_state))?;
+        for read in &self.read {
+            write!(f, ", {}", read)?;
+        }
+        write!(f, ") -> ({}", quote_if_needed(&self.to_state))?;
+        for write in &self.write {
+            write!(f, ", {}", write)?;
+        }
+        for direction in &self.moves {
+            write!(f, ", {}", direction)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// Uma transição comum é um padrão sem conjuntos, curingas nem =
+impl From<&Transition> for TransitionPattern {
+    fn from(transition: &Transition) -> Self {
+        TransitionPattern {
+            from_state: transition.from_state.clone(),
+            read: transition.read_symbols.iter().cloned().map(ReadPattern::Symbol).collect(),
+            to_state: transition.to_state.clone(),
+            write: transition.write_symbols.iter().cloned().map(WritePattern::Symbol).collect(),
+            moves: transition.moves.clone(),
+        }
+    }
+}
+
+impl TransitionPattern {
+    // Número de fitas lidas pelo padrão
+    pub fn tapes(&self) -> usize {
+        self.read.len()
+    }
+
+    // Especificidade do padrão: quanto menor, mais específico
+    pub fn specificity(&self) -> (usize, usize) {
+        let count = |kind: fn(&ReadPattern) -> bool| self.read.iter().filter(|read| kind(read)).count();
+        (count(|read| *read == ReadPattern::Any), count(|read| matches!(read, ReadPattern::Set(_))))
+    }
+
+    // Transições geradas pelo padrão, uma para cada combinação dos símbolos lidos, na
+    // ordem dos conjuntos e de Γ
+    pub fn instances(&self, tape_alphabet: &[String]) -> Vec<Transition> {
+        let mut combinations: Vec<Vec<String>> = vec![Vec::new()];
+        for read in &self.read {
+            let symbols = match read {
+                ReadPattern::Symbol(symbol) => std::slice::from_ref(symbol),
+                ReadPattern::Set(symbols) => symbols.as_slice(),
+                ReadPattern::Any => tape_alphabet,
+            };
+            combinations = combinations
+                .into_iter()
+                .flat_map(|prefix| {
+                    symbols.iter().map(move |symbol| {
+                        let mut combination = prefix.clone();
+                        combination.push(symbol.clone());
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+            .into_iter()
+            .map(|read_symbols| Transition {
+                from_state: self.from_state.clone(),
+                write_symbols: self
+                    .write
+                    .iter()
+                    .zip(&read_symbols)
+                    .map(|(write, read)| match write {
+                        WritePattern::Symbol(symbol) => symbol.clone(),
+                        WritePattern::Same => read.clone(),
+                    })
+                    .collect(),
+                read_symbols,
+                to_state: self.to_state.clone(),
+                moves: self.moves.clone(),
+            })
+            .collect()
+    }
+}
+
+// Função para expandir os padrões nas transições da máquina, na ordem dos padrões.
+// Combinações repetidas entre padrões igualmente específicos são mantidas, como
+// transições repetidas da descrição.
+pub fn expand(patterns: &[TransitionPattern], tape_alphabet: &[String]) -> Vec<Transition> {
+    let instances: Vec<Vec<Transition>> = patterns.iter().map(|pattern| pattern.instances(tape_alphabet)).collect();
+
+    // Especificidade do padrão mais específico para cada par (estado, símbolos lidos)
+    let mut most_specific: HashMap<(&str, &[String]), (usize, usize)> = HashMap::new();
+    for (pattern, transitions) in patterns.iter().zip(&instances) {
+        for transition in transitions {
+            let key = (transition.from_state.as_str(), transition.read_symbols.as_slice());
+            let specificity = most_specific.entry(key).or_insert(pattern.specificity());
+            *specificity = (*specificity).min(pattern.specificity());
+        }
+    }
+
+    let mut expanded = Vec::new();
+    for (pattern, transitions) in patterns.iter().zip(&instances) {
+        for transition in transitions {
+            let key = (transition.from_state.as_str(), transition.read_symbols.as_slice());
+            if most_specific[&key] == pattern.specificity() {
+                expanded.push(transition.clone());
+            }
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+
+    fn symbols(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn pattern(read: Vec<ReadPattern>, to_state: &str) -> TransitionPattern {
+        TransitionPattern {
+            from_state: "q0".to_string(),
+            write: vec![WritePattern::Same; read.len()],
+            moves: vec![Move::Right; read.len()],
+            read,
+            to_state: to_state.to_string(),
+        }
+    }
+
+    // Estado de destino de cada transição expandida, com os símbolos lidos
+    fn targets(patterns: &[TransitionPattern], tape_alphabet: &[String]) -> Vec<(Vec<String>, String)> {
+        let expanded = expand(patterns, tape_alphabet);
+        expanded.into_iter().map(|transition| (transition.read_symbols, transition.to_state)).collect()
+    }
+
+    #[test]
+    fn symbols_take_precedence_over_sets_and_sets_over_wildcards() {
+        let gamma = symbols(&["a", "b", "c", "B"]);
+        let patterns = [
+            pattern(vec![ReadPattern::Any], "any"),
+            pattern(vec![ReadPattern::Set(symbols(&["a", "b"]))], "set"),
+            pattern(vec![ReadPattern::Symbol("a".to_string())], "symbol"),
+        ];
+        assert_eq!(
+            targets(&patterns, &gamma),
+            vec![
+                (symbols(&["c"]), "any".to_string()),
+                (symbols(&["B"]), "any".to_string()),
+                (symbols(&["b"]), "set".to_string()),
+                (symbols(&["a"]), "symbol".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn wildcards_weigh_more_than_sets_across_tapes() {
+        let any_set = pattern(vec![ReadPattern::Any, ReadPattern::Set(symbols(&["a"]))], "any");
+        let set_set = pattern(vec![ReadPattern::Set(symbols(&["a"])), ReadPattern::Set(symbols(&["a", "B"]))], "sets");
+        let symbol_any = pattern(vec![ReadPattern::Symbol("a".to_string()), ReadPattern::Any], "symbol");
+        assert_eq!(any_set.specificity(), (1, 1));
+        assert_eq!(set_set.specificity(), (0, 2));
+        assert_eq!(symbol_any.specificity(), (1, 0));
+
+        let gamma = symbols(&["a", "B"]);
+        let targets = targets(&[any_set, set_set, symbol_any], &gamma);
+        assert!(targets.contains(&(symbols(&["a", "a"]), "sets".to_string())));
+        assert!(targets.contains(&(symbols(&["B", "a"]), "any".to_string())));
+        assert!(targets.contains(&(symbols(&["a", "B"]), "sets".to_string())));
+        assert_eq!(targets.len(), 3);
+    }
+
+    #[test]
+    fn equally_specific_patterns_keep_every_combination() {
+        let gamma = symbols(&["a", "b", "B"]);
+        let patterns = [
+            pattern(vec![ReadPattern::Set(symbols(&["a", "b"]))], "first"),
+            pattern(vec![ReadPattern::Set(symbols(&["b", "B"]))], "second"),
+        ];
+        let expanded: Vec<_> = targets(&patterns, &gamma).into_iter().filter(|(read, _)| read[0] == "b").collect();
+        assert_eq!(expanded, vec![(symbols(&["b"]), "first".to_string()), (symbols(&["b"]), "second".to_string())]);
+    }
+
+    #[test]
+    fn same_writes_back_the_symbol_read_on_each_tape() {
+        let source = "Q = {q0}\nSigma = {a}\nGamma = {a, b, B}\nq0 = q0\nF = {}\ndelta:\n\
+                      (q0, {a, b}, *) -> (q0, =, a, R, S)\n";
+        let tm = parse_description(source, "test.txt").unwrap();
+        let writes: Vec<_> = tm.transitions.iter().map(|transition| transition.write_symbols.join(" ")).collect();
+        assert_eq!(writes, ["a a", "a a", "a a", "b a", "b a", "b a"]);
+    }
+
+    #[test]
+    fn patterns_are_written_as_in_the_description() {
+        let mut written = pattern(vec![ReadPattern::Set(symbols(&["a", ","])), ReadPattern::Any], "q fim");
+        written.write[1] = WritePattern::Symbol("*".to_string());
+        assert_eq!(written.to_string(), "(q0, {a, \",\"}, *) -> (\"q fim\", =, \"*\", R, R)");
+    }
+}
@@ -0,0 +1,395 @@
+// Execução de uma máquina de Turing sobre uma palavra de entrada. A execução devolve
+// o resultado (veredito, número de passos e fita final) e, opcionalmente, escreve cada
+// configuração em um destino qualquer, no formato do arquivo de saída.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crate::stepper::{NormalizedConfiguration, Stepper};
+use crate::tape::Tape;
+use crate::validate::{validate_input_word, InvalidInputSymbol};
+use crate::TuringMachine;
+
+// Limite de passos usado quando nenhum outro é informado
+pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
+// Limite de configurações exploradas na simulação não determinística
+pub const DEFAULT_MAX_CONFIGURATIONS: u64 = 100_000;
+
+// Critério usado para decidir se a palavra é aceita
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AcceptanceMode {
+    // Aceita assim que a máquina entra em um estado final
+    OnEntry,
+    // Aceita quando a máquina para (não há transição) em um estado final, como em
+    // Hopcroft e Ullman
+    OnHalt,
+    // Convenção de Sipser: exatamente um estado de aceitação e um de rejeição, que
+    // encerram a execução ao serem alcançados
+    Sipser,
+}
+
+impl AcceptanceMode {
+    pub fn from_name(name: &str) -> Option<AcceptanceMode> {
+        match name {
+            "entry" => Some(AcceptanceMode::OnEntry),
+            "halt" => Some(AcceptanceMode::OnHalt),
+            "sipser" => Some(AcceptanceMode::Sipser),
+            _ => None,
+        }
+    }
+
+    // Verifica se a máquina pode ser executada com este critério
+    pub fn check(&self, tm: &TuringMachine) -> Result<(), String> {
+        if *self != AcceptanceMode::Sipser {
+            return Ok(());
+        }
+        if tm.accept_states.len() != 1 || tm.reject_states.len() != 1 {
+            return Err(format!(
+                "the sipser acceptance mode requires exactly one final state and one reject \
+                 state, found {} and {}",
+                tm.accept_states.len(),
+                tm.reject_states.len()
+            ));
+        }
+        if tm.accept_states == tm.reject_states {
+            return Err("the accept and reject states must be different".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Opções que limitam a execução da máquina de Turing
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    pub acceptance: AcceptanceMode,
+    pub max_steps: Option<u64>,
+    pub time_limit: Option<Duration>,
+    // Guarda todas as configurações visitadas para detectar laços infinitos. Custa
+    // memória proporcional ao número de passos, por isso vem desativado por padrão.
+    pub detect_loops: bool,
+    // Usado apenas na simulação não determinística, que explora várias ramificações
+    pub max_configurations: Option<u64>,
+    // Separador entre os símbolos da palavra de entrada. Sem ele, a palavra é dividida
+    // pelos símbolos de Σ, e é recusada se houver mais de uma divisão possível.
+    pub separator: Option<String>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            acceptance: AcceptanceMode::OnEntry,
+            max_steps: Some(DEFAULT_MAX_STEPS),
+            time_limit: None,
+            detect_loops: false,
+            max_configurations: Some(DEFAULT_MAX_CONFIGURATIONS),
+            separator: None,
+        }
+    }
+}
+
+// Motivo pelo qual uma execução foi interrompida sem aceitar nem rejeitar a palavra
+#[derive(Debug, Clone, PartialEq)]
+pub enum UndecidedReason {
+    StepLimit(u64),
+    TimeLimit(Duration),
+    ConfigurationLimit(u64),
+}
+
+// Resultado da execução da máquina de Turing
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Accepted,
+    Rejected,
+    Undecided(UndecidedReason),
+    // A configuração do passo `first_step` se repete a cada `period` passos
+    Loops { first_step: u64, period: u64 },
+}
+
+// Linha escrita ao final do arquivo de saída
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Accepted => write!(f, "aceita"),
+            Verdict::Rejected => write!(f, "rejeita"),
+            Verdict::Undecided(UndecidedReason::StepLimit(steps)) => {
+                write!(f, "indeterminado: limite de {} passos atingido", steps)
+            }
+            Verdict::Undecided(UndecidedReason::TimeLimit(limit)) => {
+                write!(f, "indeterminado: limite de tempo de {:?} atingido", limit)
+            }
+            Verdict::Undecided(UndecidedReason::ConfigurationLimit(configurations)) => {
+                write!(f, "indeterminado: limite de {} configurações atingido", configurations)
+            }
+            Verdict::Loops { first_step, period } => write!(
+                f,
+                "laço infinito: a configuração do passo {} se repete no passo {} (período {})",
+                first_step,
+                first_step + period,
+                period
+            ),
+        }
+    }
+}
+
+// Resultado completo de uma execução
+#[derive(Debug)]
+pub struct RunResult {
+    pub verdict: Verdict,
+    pub steps: u64,
+    pub final_state: String,
+    // Fitas finais, a primeira com a palavra de entrada
+    pub tapes: Vec<Tape>,
+}
+
+// Erros que impedem a execução ou a escrita das configurações
+#[derive(Debug)]
+pub enum RunError {
+    InvalidInput(InvalidInputSymbol),
+    InvalidAcceptanceMode(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::InvalidInput(error) => write!(f, "{}", error),
+            RunError::InvalidAcceptanceMode(message) => write!(f, "{}", message),
+            RunError::Io(error) => write!(f, "failed to write the output: {}", error),
+        }
+    }
+}
+
+impl From<io::Error> for RunError {
+    fn from(error: io::Error) -> Self {
+        RunError::Io(error)
+    }
+}
+
+// Verifica se a máquina pode ser executada sobre a palavra de entrada com as opções
+// dadas, sem executá-la, e devolve os símbolos da palavra
+pub fn check_run(tm: &TuringMachine, input_word: &str, options: &RunOptions) -> Result<Vec<String>, RunError> {
+    options.acceptance.check(tm).map_err(RunError::InvalidAcceptanceMode)?;
+    validate_input_word(tm, input_word, options.separator.as_deref()).map_err(RunError::InvalidInput)
+}
+
+// Executa a máquina de Turing sobre a palavra de entrada e devolve o resultado
+pub fn run(tm: &TuringMachine, input_word: &str, options: &RunOptions) -> Result<RunResult, RunError> {
+    execute(tm, input_word, options, None)
+}
+
+// Executa a máquina de Turing escrevendo em `trace` cada configuração, seguida do
+// veredito, no formato do arquivo de saída
+pub fn run_traced(
+    tm: &TuringMachine,
+    input_word: &str,
+    options: &RunOptions,
+    trace: &mut dyn Write,
+) -> Result<RunResult, RunError> {
+    let result = execute(tm, input_word, options, Some(&mut *trace))?;
+    writeln!(trace, "{}", result.verdict)?;
+    Ok(result)
+}
+
+// Função principal para executar a máquina de Turing. A execução termina quando a
+// palavra é aceita ou rejeitada, ou quando um dos limites de `options` é atingido.
+// A máquina é compilada uma única vez, pelo `Stepper`, antes da execução.
+fn execute(
+    tm: &TuringMachine,
+    input_word: &str,
+    options: &RunOptions,
+    mut trace: Option<&mut dyn Write>,
+) -> Result<RunResult, RunError> {
+    let mut stepper = Stepper::new(tm, input_word, options)?;
+    let start_time = Instant::now();
+    let mut visited: HashMap<NormalizedConfiguration, u64> = HashMap::new();
+
+    if let Some(output) = trace.as_mut() {
+        stepper.write_configuration(*output)?;
+    }
+
+    // Loop principal da execução da máquina de Turing
+    let verdict = loop {
+        // Verifica se a configuração atual, incluindo a inicial, encerra a execução
+        if let Some(verdict) = stepper.verdict() {
+            break verdict;
+        }
+
+        // Verifica se a configuração atual já ocorreu antes
+        let steps = stepper.steps();
+        if options.detect_loops {
+            match visited.entry(stepper.normalized()) {
+                Entry::Occupied(entry) => {
+                    let first_step = *entry.get();
+                    break Verdict::Loops { first_step, period: steps - first_step };
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(steps);
+                }
+            }
+        }
+
+        // Interrompe a execução se algum dos limites tiver sido atingido
+        if options.max_steps.is_some_and(|max_steps| steps >= max_steps) {
+            break Verdict::Undecided(UndecidedReason::StepLimit(steps));
+        }
+        if let Some(time_limit) = options.time_limit {
+            if steps.is_multiple_of(1024) && start_time.elapsed() >= time_limit {
+                break Verdict::Undecided(UndecidedReason::TimeLimit(time_limit));
+            }
+        }
+
+        stepper.advance();
+
+        // Escreve o estado atual da fita na saída
+        if let Some(output) = trace.as_mut() {
+            stepper.write_configuration(*output)?;
+        }
+    };
+
+    Ok(RunResult {
+        verdict,
+        steps: stepper.steps(),
+        final_state: stepper.state_name().to_string(),
+        tapes: stepper.tapes(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+    use crate::tape::format_tape;
+
+    fn machine(transitions: &str) -> TuringMachine {
+        let source = format!(
+            "Q = {{q0, q1, q2, q3}}\nSigma = {{a, b}}\nGamma = {{a, b, B}}\nq0 = q0\nF = {{q2}}\nR = {{q1}}\n\
+             delta:\n{}",
+            transitions
+        );
+        parse_description(&source, "test.txt").unwrap()
+    }
+
+    fn verdict(tm: &TuringMachine, word: &str, options: &RunOptions) -> Verdict {
+        run(tm, word, options).unwrap().verdict
+    }
+
+    fn detecting_loops() -> RunOptions {
+        RunOptions { detect_loops: true, max_steps: Some(1000), ..RunOptions::default() }
+    }
+
+    #[test]
+    fn repeated_configuration_is_a_loop() {
+        let tm = machine("(q0, B) -> (q0, B, R)\n(q0, a) -> (q0, a, L)\n");
+        assert_eq!(verdict(&tm, "a", &detecting_loops()), Verdict::Loops { first_step: 0, period: 2 });
+    }
+
+    #[test]
+    fn walking_over_blanks_is_not_a_loop() {
+        // A máquina anda para a direita para sempre: o estado e a vizinhança da cabeça se
+        // repetem, mas a posição da cabeça não
+        let tm = machine("(q0, B) -> (q0, B, R)\n(q0, a) -> (q0, a, R)\n");
+        assert_eq!(verdict(&tm, "a", &detecting_loops()), Verdict::Undecided(UndecidedReason::StepLimit(1000)));
+        let tm = machine("(q0, B) -> (q0, B, L)\n");
+        assert_eq!(verdict(&tm, "", &detecting_loops()), Verdict::Undecided(UndecidedReason::StepLimit(1000)));
+    }
+
+    #[test]
+    fn writing_while_moving_is_not_a_loop() {
+        let tm = machine("(q0, B) -> (q0, a, R)\n(q0, a) -> (q0, a, R)\n");
+        let options = RunOptions { max_steps: Some(5), ..detecting_loops() };
+        let result = run(&tm, "", &options).unwrap();
+        assert_eq!(result.verdict, Verdict::Undecided(UndecidedReason::StepLimit(5)));
+        assert_eq!(format_tape(&result.tapes[0], &result.final_state), "aaaaa{q0}B");
+    }
+
+    #[test]
+    fn tape_grows_to_the_left_of_the_initial_blank() {
+        let tm = machine("(q0, B) -> (q3, b, L)\n(q3, B) -> (q2, b, S)\n");
+        let result = run(&tm, "a", &RunOptions::default()).unwrap();
+        assert_eq!(result.verdict, Verdict::Accepted);
+        assert_eq!(format_tape(&result.tapes[0], &result.final_state), "{q2}bbaB");
+    }
+
+    // Aceita "a" ao entrar em q2; com o critério de parada, continua até parar em q2
+    const ACCEPTS_A: &str = "(q0, B) -> (q3, B, R)\n(q3, a) -> (q2, a, R)\n(q2, B) -> (q3, b, L)\n\
+                             (q3, b) -> (q1, b, R)\n(q1, B) -> (q2, B, S)\n";
+
+    fn with_mode(acceptance: AcceptanceMode) -> RunOptions {
+        RunOptions { acceptance, ..RunOptions::default() }
+    }
+
+    #[test]
+    fn accepts_on_entering_a_final_state() {
+        let result = run(&machine(ACCEPTS_A), "a", &RunOptions::default()).unwrap();
+        assert_eq!(result.verdict, Verdict::Accepted);
+        assert_eq!(result.steps, 2);
+        assert_eq!(result.final_state, "q2");
+        assert_eq!(format_tape(&result.tapes[0], &result.final_state), "Ba{q2}B");
+    }
+
+    #[test]
+    fn rejects_on_entering_a_reject_state() {
+        let tm = machine(ACCEPTS_A);
+        for mode in [AcceptanceMode::OnEntry, AcceptanceMode::OnHalt, AcceptanceMode::Sipser] {
+            let result = run(&tm, "b", &with_mode(mode)).unwrap();
+            assert_eq!(result.verdict, Verdict::Rejected);
+            assert_eq!((result.steps, result.final_state.as_str()), (2, "q1"));
+        }
+    }
+
+    #[test]
+    fn halting_without_a_transition_outside_final_states_rejects() {
+        // Em q3 lendo o branco não há transição
+        let tm = machine(ACCEPTS_A);
+        for mode in [AcceptanceMode::OnEntry, AcceptanceMode::OnHalt, AcceptanceMode::Sipser] {
+            let result = run(&tm, "", &with_mode(mode)).unwrap();
+            assert_eq!(result.verdict, Verdict::Rejected);
+            assert_eq!((result.steps, result.final_state.as_str()), (1, "q3"));
+        }
+    }
+
+    #[test]
+    fn halting_acceptance_waits_for_the_machine_to_stop() {
+        let result = run(&machine(ACCEPTS_A), "a", &with_mode(AcceptanceMode::OnHalt)).unwrap();
+        assert_eq!(result.verdict, Verdict::Accepted);
+        assert_eq!(result.steps, 4);
+        assert_eq!(format_tape(&result.tapes[0], &result.final_state), "Ba{q2}b");
+    }
+
+    #[test]
+    fn sipser_acceptance_stops_at_the_accept_state() {
+        let result = run(&machine(ACCEPTS_A), "a", &with_mode(AcceptanceMode::Sipser)).unwrap();
+        assert_eq!((result.verdict, result.steps), (Verdict::Accepted, 2));
+    }
+
+    #[test]
+    fn sipser_acceptance_requires_one_accept_and_one_reject_state() {
+        let mut tm = machine(ACCEPTS_A);
+        assert_eq!(AcceptanceMode::Sipser.check(&tm), Ok(()));
+
+        tm.reject_states.clear();
+        let error = run(&tm, "a", &with_mode(AcceptanceMode::Sipser)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the sipser acceptance mode requires exactly one final state and one reject state, found 1 and 0"
+        );
+        assert!(AcceptanceMode::OnEntry.check(&tm).is_ok());
+        assert!(AcceptanceMode::OnHalt.check(&tm).is_ok());
+
+        tm.reject_states = tm.accept_states.clone();
+        let error = AcceptanceMode::Sipser.check(&tm).unwrap_err();
+        assert_eq!(error, "the accept and reject states must be different");
+    }
+
+    #[test]
+    fn acceptance_modes_are_named_on_the_command_line() {
+        assert_eq!(AcceptanceMode::from_name("entry"), Some(AcceptanceMode::OnEntry));
+        assert_eq!(AcceptanceMode::from_name("halt"), Some(AcceptanceMode::OnHalt));
+        assert_eq!(AcceptanceMode::from_name("sipser"), Some(AcceptanceMode::Sipser));
+        assert_eq!(AcceptanceMode::from_name("final"), None);
+    }
+}
@@ -0,0 +1,326 @@
+// Execução passo a passo de uma máquina de Turing. O `Stepper` guarda a configuração
+// atual e avança um passo por vez, devolvendo a transição usada e a nova configuração,
+// de modo que quem o usa pode interromper a execução, inspecionar o estado ou montar
+// sua própria visualização sem interpretar o arquivo de saída.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::compiled::{CompiledMachine, StateId, SymbolId};
+use crate::run::{check_run, AcceptanceMode, RunError, RunOptions, Verdict};
+use crate::tape::{format_tapes_with, Tape};
+use crate::{Transition, TuringMachine};
+
+// Configuração da máquina: o estado atual e as fitas, cada uma com a posição da sua
+// cabeça. A primeira fita é a que recebe a palavra de entrada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Configuration {
+    pub state: String,
+    pub tapes: Vec<Tape>,
+}
+
+impl Configuration {
+    // Posição de cada cabeça de leitura/escrita entre as células guardadas da sua fita
+    pub fn heads(&self) -> Vec<usize> {
+        self.tapes.iter().map(Tape::head).collect()
+    }
+
+    // Símbolos sob as cabeças de leitura/escrita
+    pub fn symbols(&self) -> Vec<String> {
+        self.tapes.iter().map(Tape::read).collect()
+    }
+}
+
+// Mesmo formato das linhas do arquivo de saída
+impl fmt::Display for Configuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_tapes_with(&self.tapes, &self.state, |symbol| symbol))
+    }
+}
+
+// Configuração com as fitas normalizadas, sem os brancos das extremidades e com as
+// posições contadas a partir do branco inicial. Duas configurações iguais nesta forma
+// são exatamente a mesma configuração da máquina.
+pub(crate) type NormalizedConfiguration = (StateId, Vec<(Vec<SymbolId>, isize, isize)>);
+
+// Um passo da execução: a transição usada e a configuração resultante
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub transition: Transition,
+    pub configuration: Configuration,
+}
+
+// Função que verifica se o estado atual encerra a execução, de acordo com o critério
+// de aceitação. Estados de rejeição encerram a execução em qualquer critério.
+fn halting_verdict(machine: &CompiledMachine, state: StateId, mode: AcceptanceMode) -> Option<Verdict> {
+    if machine.is_rejecting(state) {
+        Some(Verdict::Rejected)
+    } else if machine.is_accepting(state) && mode != AcceptanceMode::OnHalt {
+        Some(Verdict::Accepted)
+    } else {
+        None
+    }
+}
+
+// Função que calcula o veredito de uma configuração, se ela encerrar a execução, a
+// partir do resultado da consulta pelas suas transições. Sem transição, a máquina
+// para: a palavra é aceita apenas se o critério for parar em um estado final e ele
+// tiver sido alcançado.
+pub(crate) fn configuration_verdict(
+    machine: &CompiledMachine,
+    state: StateId,
+    has_transition: bool,
+    mode: AcceptanceMode,
+) -> Option<Verdict> {
+    if let Some(verdict) = halting_verdict(machine, state, mode) {
+        return Some(verdict);
+    }
+    if has_transition {
+        None
+    } else if mode == AcceptanceMode::OnHalt && machine.is_accepting(state) {
+        Some(Verdict::Accepted)
+    } else {
+        Some(Verdict::Rejected)
+    }
+}
+
+// Guarda em `symbols` os símbolos sob as cabeças das fitas, reaproveitando a memória
+// já alocada
+pub(crate) fn read_symbols(tapes: &[Tape<SymbolId>], symbols: &mut Vec<SymbolId>) {
+    symbols.clear();
+    symbols.extend(tapes.iter().map(Tape::read));
+}
+
+// Fitas da configuração inicial: os símbolos da palavra de entrada vão para a primeira
+// fita e as demais começam em branco
+pub(crate) fn initial_tapes(machine: &CompiledMachine, input_symbols: &[String]) -> Vec<Tape<SymbolId>> {
+    let input_symbols = input_symbols
+        .iter()
+        .map(|symbol| machine.symbol_id(symbol).expect("input symbols are interned from Sigma"));
+    let mut tapes = vec![Tape::new(input_symbols, machine.blank())];
+    tapes.resize(machine.tapes(), Tape::new([], machine.blank()));
+    tapes
+}
+
+// Converte uma configuração da máquina compilada, com os nomes dos símbolos
+pub(crate) fn to_configuration(machine: &CompiledMachine, state: StateId, tapes: &[Tape<SymbolId>]) -> Configuration {
+    Configuration {
+        state: machine.state_name(state).to_string(),
+        tapes: tapes.iter().map(|tape| tape.map(|symbol| machine.symbol(symbol).to_string())).collect(),
+    }
+}
+
+// Executor passo a passo. Como iterador, produz um `Step` para cada transição usada e
+// termina quando a máquina aceita ou rejeita a palavra; os limites de passos e de tempo
+// ficam a cargo de quem o usa.
+pub struct Stepper {
+    machine: CompiledMachine,
+    tapes: Vec<Tape<SymbolId>>,
+    state: StateId,
+    steps: u64,
+    acceptance: AcceptanceMode,
+    // Símbolos sob as cabeças na configuração atual
+    read_symbols: Vec<SymbolId>,
+    // Índice da transição a partir da configuração atual, consultado uma única vez a
+    // cada passo e usado tanto pelo veredito quanto pelo passo seguinte
+    next: Option<usize>,
+}
+
+impl Stepper {
+    // Prepara a execução da máquina sobre a palavra de entrada, a partir da
+    // configuração inicial. Apenas o critério de aceitação das opções é usado.
+    pub fn new(tm: &TuringMachine, input_word: &str, options: &RunOptions) -> Result<Stepper, RunError> {
+        let input_symbols = check_run(tm, input_word, options)?;
+
+        let machine = CompiledMachine::compile(tm);
+        let tapes = initial_tapes(&machine, &input_symbols);
+        let state = machine.initial_state();
+        let mut stepper = Stepper {
+            machine,
+            tapes,
+            state,
+            steps: 0,
+            acceptance: options.acceptance,
+            read_symbols: Vec::new(),
+            next: None,
+        };
+        stepper.look_up();
+        Ok(stepper)
+    }
+
+    // Consulta a transição a partir da configuração atual
+    fn look_up(&mut self) {
+        read_symbols(&self.tapes, &mut self.read_symbols);
+        self.next = self.machine.first_transition(self.state, &self.read_symbols);
+    }
+
+    // Configuração atual
+    pub fn configuration(&self) -> Configuration {
+        to_configuration(&self.machine, self.state, &self.tapes)
+    }
+
+    pub fn state_name(&self) -> &str {
+        self.machine.state_name(self.state)
+    }
+
+    // Número de passos já executados
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    // Veredito da configuração atual, se ela encerrar a execução
+    pub fn verdict(&self) -> Option<Verdict> {
+        configuration_verdict(&self.machine, self.state, self.next.is_some(), self.acceptance)
+    }
+
+    // Executa um passo, devolvendo a transição usada e a nova configuração, ou `None`
+    // se a execução já tiver terminado
+    pub fn step(&mut self) -> Option<Step> {
+        if self.verdict().is_some() {
+            return None;
+        }
+        let compiled = self.machine.transition_at(self.next?);
+        let transition = Transition {
+            from_state: self.state_name().to_string(),
+            read_symbols: self.read_symbols.iter().map(|&symbol| self.machine.symbol(symbol).to_string()).collect(),
+            to_state: self.machine.state_name(compiled.to_state).to_string(),
+            write_symbols: compiled
+                .write_symbols
+                .iter()
+                .map(|&symbol| self.machine.symbol(symbol).to_string())
+                .collect(),
+            moves: compiled.moves.clone(),
+        };
+        self.advance();
+        Some(Step { transition, configuration: self.configuration() })
+    }
+
+    // Executa um passo sem montar a configuração resultante. Devolve falso se a
+    // execução já tiver terminado.
+    pub(crate) fn advance(&mut self) -> bool {
+        if self.verdict().is_some() {
+            return false;
+        }
+        let Some(index) = self.next else {
+            return false;
+        };
+
+        // Atualiza as fitas e o estado
+        let transition = self.machine.transition_at(index);
+        transition.apply(&mut self.tapes);
+        self.state = transition.to_state;
+        self.steps += 1;
+        self.look_up();
+        true
+    }
+
+    // Configuração atual na forma normalizada das fitas, usada para detectar laços
+    pub(crate) fn normalized(&self) -> NormalizedConfiguration {
+        (self.state, self.tapes.iter().map(Tape::normalized).collect())
+    }
+
+    // Função para escrever a configuração atual na saída
+    pub(crate) fn write_configuration(&self, output: &mut dyn Write) -> io::Result<()> {
+        let line = format_tapes_with(&self.tapes, self.state_name(), |symbol| self.machine.symbol(symbol));
+        writeln!(output, "{}", line)
+    }
+
+    // Fitas atuais, com os nomes dos símbolos
+    pub fn tapes(&self) -> Vec<Tape> {
+        to_configuration(&self.machine, self.state, &self.tapes).tapes
+    }
+}
+
+impl Iterator for Stepper {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+
+    // Troca os a por X até o primeiro branco e aceita em q2; com o critério de parada,
+    // a máquina continua em q2 trocando os X de volta por a
+    fn machine() -> TuringMachine {
+        let source = "Q = {q0, q1, q2}\nSigma = {a}\nGamma = {a, X, B}\nq0 = q0\nF = {q2}\ndelta:\n\
+                      (q0, B) -> (q1, B, R)\n(q1, a) -> (q1, X, R)\n(q1, B) -> (q2, B, L)\n(q2, X) -> (q2, a, L)\n";
+        parse_description(source, "test.txt").unwrap()
+    }
+
+    fn steps(stepper: &mut Stepper) -> Vec<String> {
+        stepper.map(|step| format!("{}: {}", step.transition, step.configuration)).collect()
+    }
+
+    #[test]
+    fn steps_through_the_computation() {
+        let mut stepper = Stepper::new(&machine(), "aa", &RunOptions::default()).unwrap();
+        assert_eq!(stepper.configuration().to_string(), "{q0}BaaB");
+        assert_eq!(stepper.verdict(), None);
+
+        let step = stepper.step().unwrap();
+        assert_eq!(step.transition.to_string(), "(q0, B) -> (q1, B, R)");
+        assert_eq!(step.configuration.symbols(), ["a"]);
+        assert_eq!(
+            steps(&mut stepper),
+            [
+                "(q1, a) -> (q1, X, R): BX{q1}aB",
+                "(q1, a) -> (q1, X, R): BXX{q1}B",
+                "(q1, B) -> (q2, B, L): BX{q2}XB",
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_with_the_final_verdict() {
+        let mut stepper = Stepper::new(&machine(), "aa", &RunOptions::default()).unwrap();
+        while stepper.advance() {}
+        assert_eq!(stepper.verdict(), Some(Verdict::Accepted));
+        assert_eq!(stepper.steps(), 4);
+        assert_eq!(stepper.state_name(), "q2");
+        assert!(stepper.step().is_none());
+    }
+
+    #[test]
+    fn halting_without_a_transition_rejects() {
+        let source = "Q = {q0, q1}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {q1}\ndelta:\n(q0, B) -> (q0, B, R)\n";
+        let tm = parse_description(source, "test.txt").unwrap();
+        let mut stepper = Stepper::new(&tm, "a", &RunOptions::default()).unwrap();
+        assert_eq!(stepper.by_ref().count(), 1);
+        assert_eq!(stepper.verdict(), Some(Verdict::Rejected));
+        assert_eq!(stepper.configuration().to_string(), "B{q0}aB");
+    }
+
+    #[test]
+    fn halting_acceptance_keeps_running_in_accepting_states() {
+        let options = RunOptions { acceptance: AcceptanceMode::OnHalt, ..RunOptions::default() };
+        let mut stepper = Stepper::new(&machine(), "a", &options).unwrap();
+        assert_eq!(
+            steps(&mut stepper),
+            [
+                "(q0, B) -> (q1, B, R): B{q1}aB",
+                "(q1, a) -> (q1, X, R): BX{q1}B",
+                "(q1, B) -> (q2, B, L): B{q2}XB",
+                "(q2, X) -> (q2, a, L): {q2}BaB",
+            ]
+        );
+        assert_eq!(stepper.verdict(), Some(Verdict::Accepted));
+    }
+
+    #[test]
+    fn multi_tape_machines_step_through_the_sparse_table() {
+        let source = "Q = {q0, q1, q2}\nSigma = {a}\nGamma = {a, B}\nq0 = q0\nF = {q2}\ndelta:\n\
+                      (q0, B, B) -> (q1, B, B, R, R)\n(q1, a, B) -> (q1, a, a, R, R)\n(q1, B, B) -> (q2, B, B, S, L)\n";
+        let tm = parse_description(source, "test.txt").unwrap();
+        let mut stepper = Stepper::new(&tm, "aa", &RunOptions::default()).unwrap();
+        let last = stepper.by_ref().last().unwrap();
+        assert_eq!(last.transition.to_string(), "(q1, B, B) -> (q2, B, B, S, L)");
+        assert_eq!(stepper.steps(), 4);
+        assert_eq!(stepper.verdict(), Some(Verdict::Accepted));
+        assert_eq!(last.configuration.heads(), [3, 2]);
+    }
+}
@@ -0,0 +1,226 @@
+// Símbolos da fita. Um símbolo é um texto qualquer, não vazio: um caractere, como a ou
+// B, um nome com vários caracteres, como X1 ou #a, ou um grafema formado por vários
+// pontos de código, como uma letra acentuada com um acento combinante ou um emoji.
+//
+// A palavra de entrada é dividida em símbolos de Σ. Sem separador, a divisão deve ser
+// única: com Σ = {ab, a, bc}, a palavra abc é lida como a·bc, e com Σ = {a, aa}, a
+// palavra aa é ambígua e recusada. Com um separador, como em --separator ' ', cada
+// trecho entre separadores é um símbolo.
+
+use std::collections::HashSet;
+
+use crate::validate::InvalidInputSymbol;
+
+// Verifica se o caractere se junta ao anterior no mesmo grafema. É uma aproximação das
+// regras de UAX #29 que cobre os casos comuns (acentos combinantes, seletores de
+// variação, modificadores de emoji e etiquetas) sem depender de tabelas Unicode.
+fn is_extending(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200C
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0020..=0xE007F
+            | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+// Função para dividir o texto em grafemas, cada um com a posição, em bytes, em que
+// começa. Além dos caracteres que estendem o anterior, junta as sequências ligadas por
+// ZWJ (U+200D) e os pares de indicadores regionais das bandeiras.
+pub fn grapheme_indices(text: &str) -> Vec<(usize, &str)> {
+    let mut graphemes = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, first)) = chars.next() {
+        let mut previous = first;
+        let mut regional_pair = is_regional_indicator(first);
+        while let Some(&(_, c)) = chars.peek() {
+            let joins = is_extending(c)
+                || c == '\u{200D}'
+                || previous == '\u{200D}'
+                || (regional_pair && is_regional_indicator(c));
+            if !joins {
+                break;
+            }
+            regional_pair = false;
+            previous = c;
+            chars.next();
+        }
+        let end = chars.peek().map_or(text.len(), |&(end, _)| end);
+        graphemes.push((start, &text[start..end]));
+    }
+    graphemes
+}
+
+// Verifica se o símbolo é um único grafema, exibido na fita sem aspas
+pub fn is_single_grapheme(symbol: &str) -> bool {
+    grapheme_indices(symbol).len() == 1
+}
+
+// Escreve o texto entre aspas duplas, com \ antes de cada aspa e de cada barra
+// invertida
+pub fn quoted(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() + 2);
+    output.push('"');
+    for c in text.chars() {
+        if c == '"' || c == '\\' {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output.push('"');
+    output
+}
+
+// Função para dividir a palavra de entrada em símbolos. Cada símbolo vem com a coluna,
+// em caracteres e a partir de 1, em que começa na palavra. Com um separador, os trechos
+// são devolvidos como estão, e a validação verifica se pertencem a Σ. Sem separador, a
+// palavra deve ter exatamente uma divisão em símbolos de Σ; caso contrário, o erro
+// aponta o primeiro grafema que nenhuma divisão alcança ou a primeira posição em que
+// duas divisões se separam.
+pub fn split_input_word<'w>(
+    word: &'w str,
+    alphabet: &[String],
+    separator: Option<&str>,
+) -> Result<Vec<(usize, &'w str)>, InvalidInputSymbol> {
+    let column = |offset: usize| word[..offset].chars().count() + 1;
+
+    if let Some(separator) = separator.filter(|separator| !separator.is_empty()) {
+        let mut symbols = Vec::new();
+        let mut start = 0;
+        let ends = word.match_indices(separator).map(|(end, _)| end).chain([word.len()]);
+        for end in ends {
+            if end > start {
+                symbols.push((column(start), &word[start..end]));
+            }
+            start = end + separator.len();
+        }
+        return Ok(symbols);
+    }
+
+    let graphemes = grapheme_indices(word);
+    let count = graphemes.len();
+    let longest = alphabet.iter().map(|symbol| grapheme_indices(symbol).len()).max().unwrap_or(1);
+    let members: HashSet<&str> = alphabet.iter().map(String::as_str).collect();
+    let end_of = |i: usize| graphemes.get(i).map_or(word.len(), |&(offset, _)| offset);
+    // Comprimentos, em grafemas, dos símbolos de Σ que começam no grafema i, do mais
+    // longo para o mais curto
+    let lengths = |i: usize| {
+        let (members, end_of) = (&members, &end_of);
+        let start = end_of(i);
+        (1..=longest.min(count - i)).rev().filter(move |&length| members.contains(&word[start..end_of(i + length)]))
+    };
+
+    // Número de divisões do restante da palavra a partir de cada grafema, limitado a 2
+    let mut splits = vec![0u8; count + 1];
+    splits[count] = 1;
+    for i in (0..count).rev() {
+        splits[i] = lengths(i).fold(0, |total, length| (total + splits[i + length]).min(2));
+    }
+
+    if splits[0] == 0 {
+        // O grafema mais à frente alcançado por alguma divisão do começo da palavra não
+        // inicia nenhum símbolo de Σ
+        let mut reached = vec![false; count + 1];
+        reached[0] = true;
+        for i in 0..count {
+            if reached[i] {
+                for length in lengths(i) {
+                    reached[i + length] = true;
+                }
+            }
+        }
+        let i = (0..count).rev().find(|&i| reached[i]).unwrap_or(0);
+        let (offset, symbol) = graphemes[i];
+        return Err(InvalidInputSymbol { position: column(offset), symbol: symbol.to_string(), ambiguous_with: None });
+    }
+
+    let mut symbols = Vec::new();
+    let mut i = 0;
+    while i < count {
+        let mut choices = lengths(i).filter(|&length| splits[i + length] > 0);
+        let length = choices.next().unwrap_or(1);
+        let start = end_of(i);
+        if let Some(other) = choices.next() {
+            return Err(InvalidInputSymbol {
+                position: column(start),
+                symbol: word[start..end_of(i + length)].to_string(),
+                ambiguous_with: Some(word[start..end_of(i + other)].to_string()),
+            });
+        }
+        symbols.push((column(start), &word[start..end_of(i + length)]));
+        i += length;
+    }
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alphabet(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|symbol| symbol.to_string()).collect()
+    }
+
+    fn split<'w>(word: &'w str, symbols: &[&str]) -> Result<Vec<(usize, &'w str)>, InvalidInputSymbol> {
+        split_input_word(word, &alphabet(symbols), None)
+    }
+
+    #[test]
+    fn words_are_split_even_when_the_longest_symbol_is_a_dead_end() {
+        assert_eq!(split("abc", &["ab", "a", "bc"]), Ok(vec![(1, "a"), (2, "bc")]));
+        assert_eq!(split("X1a,X1", &["X1", "a", ","]), Ok(vec![(1, "X1"), (3, "a"), (4, ","), (5, "X1")]));
+        assert_eq!(split("", &["a"]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn ambiguous_words_are_refused() {
+        let error = split("baa", &["a", "aa", "b"]).unwrap_err();
+        assert_eq!(error.position, 2);
+        assert_eq!(error.symbol, "aa");
+        assert_eq!(error.ambiguous_with.as_deref(), Some("a"));
+        // Com um separador, a divisão é a dada
+        assert_eq!(split_input_word("a aa", &alphabet(&["a", "aa"]), Some(" ")), Ok(vec![(1, "a"), (3, "aa")]));
+    }
+
+    #[test]
+    fn the_error_points_at_the_first_grapheme_no_split_reaches() {
+        let error = split("abcd", &["ab", "a", "bc"]).unwrap_err();
+        assert_eq!((error.position, error.symbol.as_str(), error.ambiguous_with), (4, "d", None));
+        let error = split("xa", &["a"]).unwrap_err();
+        assert_eq!((error.position, error.symbol.as_str()), (1, "x"));
+    }
+
+    #[test]
+    fn symbols_end_on_grapheme_boundaries() {
+        let word = "e\u{301}e";
+        assert_eq!(split(word, &["e\u{301}", "e"]), Ok(vec![(1, "e\u{301}"), (3, "e")]));
+        let error = split(word, &["e"]).unwrap_err();
+        assert_eq!(error.symbol, "e\u{301}");
+        assert_eq!(grapheme_indices("a🇧🇷👩‍💻"), vec![(0, "a"), (1, "🇧🇷"), (9, "👩‍💻")]);
+    }
+
+    #[test]
+    fn separators_skip_empty_pieces() {
+        let pieces = split_input_word("X1  a ", &alphabet(&["X1", "a"]), Some(" "));
+        assert_eq!(pieces, Ok(vec![(1, "X1"), (5, "a")]));
+    }
+
+    #[test]
+    fn quotes_and_backslashes_are_escaped() {
+        assert_eq!(quoted("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    }
+}
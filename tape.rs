@@ -0,0 +1,194 @@
+// Fita da máquina de Turing, infinita nos dois sentidos. Apenas a região já visitada
+// pela cabeça (mais a palavra de entrada) é guardada em memória; ao mover a cabeça
+// para além dessa região, a fita é estendida com símbolos brancos.
+//
+// As células guardam os nomes dos símbolos por padrão, mas a fita também pode guardar
+// os identificadores numéricos de símbolos usados pela máquina compilada.
+
+use std::collections::VecDeque;
+
+use crate::symbol::{is_single_grapheme, quoted};
+
+// Símbolo branco usado quando a descrição não declara outro
+pub const DEFAULT_BLANK: &str = "B";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tape<S = String> {
+    cells: VecDeque<S>,
+    head: usize,
+    blank: S,
+    // Células acrescentadas à esquerda do branco inicial, que fica na posição 0
+    left: usize,
+}
+
+impl<S: Clone + PartialEq> Tape<S> {
+    // Inicializa a fita com a palavra de entrada entre dois brancos, com a cabeça de
+    // leitura/escrita sobre o branco à esquerda da palavra
+    pub fn new(input_word: impl IntoIterator<Item = S>, blank: S) -> Tape<S> {
+        let mut cells = VecDeque::new();
+        cells.push_back(blank.clone());
+        cells.extend(input_word);
+        cells.push_back(blank.clone());
+        Tape { cells, head: 0, blank, left: 0 }
+    }
+
+    // Símbolo sob a cabeça de leitura/escrita
+    pub fn read(&self) -> S {
+        self.cells[self.head].clone()
+    }
+
+    pub fn write(&mut self, symbol: S) {
+        self.cells[self.head] = symbol;
+    }
+
+    pub fn move_left(&mut self) {
+        if self.head == 0 {
+            self.cells.push_front(self.blank.clone());
+            self.left += 1;
+        } else {
+            self.head -= 1;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        self.head += 1;
+        if self.head == self.cells.len() {
+            self.cells.push_back(self.blank.clone());
+        }
+    }
+
+    // Posição da cabeça de leitura/escrita entre as células guardadas
+    pub fn head(&self) -> usize {
+        self.head
+    }
+
+    // Células já visitadas (mais a palavra de entrada), da esquerda para a direita
+    pub fn cells(&self) -> impl Iterator<Item = S> + '_ {
+        self.cells.iter().cloned()
+    }
+
+    // Converte cada célula da fita, mantendo a posição da cabeça
+    pub fn map<T: Clone + PartialEq>(&self, f: impl Fn(S) -> T) -> Tape<T> {
+        Tape {
+            cells: self.cells.iter().map(|symbol| f(symbol.clone())).collect(),
+            head: self.head,
+            blank: f(self.blank.clone()),
+            left: self.left,
+        }
+    }
+
+    // Posição da cabeça de leitura/escrita na fita, contada a partir do branco inicial,
+    // à esquerda da palavra de entrada
+    pub fn position(&self) -> isize {
+        self.head as isize - self.left as isize
+    }
+
+    // Conteúdo da fita sem os brancos das extremidades, com a posição em que começa, e a
+    // posição da cabeça, ambas contadas a partir do branco inicial. Duas fitas com o
+    // mesmo resultado são a mesma fita, mesmo que tenham sido estendidas de formas
+    // diferentes; o mesmo conteúdo em outro lugar da fita, ou com a cabeça em outra
+    // posição, é outra fita.
+    pub fn normalized(&self) -> (Vec<S>, isize, isize) {
+        let start = self.cells.iter().position(|symbol| *symbol != self.blank);
+        let Some(start) = start else {
+            return (Vec::new(), 0, self.position());
+        };
+        let end = self.cells.iter().rposition(|symbol| *symbol != self.blank).unwrap_or(start);
+        let cells = self.cells.range(start..=end).cloned().collect();
+        (cells, start as isize - self.left as isize, self.position())
+    }
+}
+
+// Função para formatar a fita para exibição, com o estado atual entre chaves antes do
+// símbolo sob a cabeça. `symbol_name` converte cada célula no nome do símbolo; os
+// símbolos com mais de um grafema (e a própria aspa) aparecem entre aspas, como em
+// {q0}B"X1"aB, para que as células continuem distinguíveis.
+pub fn format_tape_with<S: Clone, N: AsRef<str>>(
+    tape: &Tape<S>,
+    current_state: &str,
+    symbol_name: impl Fn(S) -> N,
+) -> String {
+    let mut output = String::new();
+    for (i, symbol) in tape.cells.iter().enumerate() {
+        if i == tape.head {
+            output.push('{');
+            output.push_str(current_state);
+            output.push('}');
+        }
+        let name = symbol_name(symbol.clone());
+        let name = name.as_ref();
+        if is_single_grapheme(name) && name != "\"" {
+            output.push_str(name);
+        } else {
+            output.push_str(&quoted(name));
+        }
+    }
+    output
+}
+
+// Função para formatar as fitas de uma máquina de várias fitas, separadas por ' | ',
+// cada uma com o estado atual antes do símbolo sob a sua cabeça. Com uma única fita, o
+// resultado é o mesmo de `format_tape_with`.
+pub fn format_tapes_with<S: Clone, N: AsRef<str>>(
+    tapes: &[Tape<S>],
+    current_state: &str,
+    symbol_name: impl Fn(S) -> N,
+) -> String {
+    let tapes: Vec<String> =
+        tapes.iter().map(|tape| format_tape_with(tape, current_state, &symbol_name)).collect();
+    tapes.join(" | ")
+}
+
+// Função para formatar a fita da máquina de Turing para exibição
+pub fn format_tape(tape: &Tape, current_state: &str) -> String {
+    format_tape_with(tape, current_state, |symbol| symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tape(word: &str) -> Tape {
+        Tape::new(word.chars().map(String::from), DEFAULT_BLANK.to_string())
+    }
+
+    #[test]
+    fn tape_grows_with_blanks_to_the_left_and_right() {
+        let mut tape = tape("ab");
+        tape.move_left();
+        tape.move_left();
+        assert_eq!((tape.head(), tape.position()), (0, -2));
+        tape.write("X".to_string());
+        for _ in 0..6 {
+            tape.move_right();
+        }
+        assert_eq!(tape.position(), 4);
+        assert_eq!(tape.read(), "B");
+        assert_eq!(format_tape(&tape, "q"), "XBBabB{q}B");
+    }
+
+    #[test]
+    fn normalized_tape_keeps_absolute_positions() {
+        let mut tape = tape("ab");
+        assert_eq!(tape.normalized(), (vec!["a".to_string(), "b".to_string()], 1, 0));
+        // Estender a fita com brancos não muda a forma normalizada
+        let mut extended = tape.clone();
+        extended.move_left();
+        extended.move_right();
+        assert_ne!(extended, tape);
+        assert_eq!(extended.normalized(), tape.normalized());
+        // O mesmo conteúdo com a cabeça em outra posição é outra fita
+        tape.move_right();
+        assert_eq!(tape.normalized(), (vec!["a".to_string(), "b".to_string()], 1, 1));
+    }
+
+    #[test]
+    fn blank_tapes_differ_by_the_head_position() {
+        let mut tape = tape("");
+        let before = tape.normalized();
+        tape.move_right();
+        tape.move_right();
+        assert_eq!(before, (Vec::new(), 0, 0));
+        assert_ne!(tape.normalized(), before);
+    }
+}
@@ -0,0 +1,234 @@
+// Modelos de transições (macros) da descrição. Um bloco for repete as transições do seu
+// corpo para cada valor das variáveis, o que gera famílias de estados que lembram um
+// símbolo, como carry[a], carry[b] e carry[c]:
+//
+//     for x in {a, b, c} {
+//         (q0, x) -> (carry[x], B, R)
+//         (carry[x], *) -> (carry[x], =, R)
+//         (carry[x], B) -> (back, x, L)
+//     }
+//
+// Com mais de uma variável, como em for x in {a, b}, y in {a, b} { ... }, o corpo é
+// repetido para cada combinação, com a primeira variável variando mais devagar. Nas
+// transições do corpo, um nome igual a uma variável é trocado pelo seu valor, assim
+// como cada [x] dentro de um nome. Os estados gerados dessa forma são acrescentados a Q.
+// Uma transição do corpo que não usa todas as variáveis gera a mesma transição mais de
+// uma vez; as cópias são descartadas.
+
+use std::ops::Range;
+
+use crate::pattern::{ReadPattern, TransitionPattern, WritePattern};
+
+// Bloco for da descrição: as variáveis, cada uma com os seus valores, e o intervalo das
+// transições do corpo entre as transições como escritas na descrição
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub bindings: Vec<(String, Vec<String>)>,
+    pub transitions: Range<usize>,
+}
+
+impl Template {
+    // Valores das variáveis em cada repetição do corpo
+    fn assignments(&self) -> Vec<Vec<(&str, &str)>> {
+        let mut assignments: Vec<Vec<(&str, &str)>> = vec![Vec::new()];
+        for (variable, values) in &self.bindings {
+            assignments = assignments
+                .into_iter()
+                .flat_map(|prefix| {
+                    values.iter().map(move |value| {
+                        let mut assignment = prefix.clone();
+                        assignment.push((variable.as_str(), value.as_str()));
+                        assignment
+                    })
+                })
+                .collect();
+        }
+        assignments
+    }
+
+    // Transições geradas por uma transição do corpo, uma para cada repetição
+    pub fn instances(&self, transition: &TransitionPattern) -> Vec<TransitionPattern> {
+        self.assignments().iter().map(|assignment| instantiate(transition, assignment)).collect()
+    }
+}
+
+// Troca as variáveis de um nome pelos seus valores: o nome inteiro, se ele for uma
+// variável, ou cada [x] dentro dele
+fn substitute(name: &str, assignment: &[(&str, &str)]) -> String {
+    if let Some(&(_, value)) = assignment.iter().find(|&&(variable, _)| variable == name) {
+        return value.to_string();
+    }
+    let mut name = name.to_string();
+    for &(variable, value) in assignment {
+        name = name.replace(&format!("[{}]", variable), &format!("[{}]", value));
+    }
+    name
+}
+
+fn instantiate(transition: &TransitionPattern, assignment: &[(&str, &str)]) -> TransitionPattern {
+    let read = transition.read.iter().map(|read| match read {
+        ReadPattern::Symbol(symbol) => ReadPattern::Symbol(substitute(symbol, assignment)),
+        ReadPattern::Set(symbols) => {
+            ReadPattern::Set(symbols.iter().map(|symbol| substitute(symbol, assignment)).collect())
+        }
+        ReadPattern::Any => ReadPattern::Any,
+    });
+    let write = transition.write.iter().map(|write| match write {
+        WritePattern::Symbol(symbol) => WritePattern::Symbol(substitute(symbol, assignment)),
+        WritePattern::Same => WritePattern::Same,
+    });
+    TransitionPattern {
+        from_state: substitute(&transition.from_state, assignment),
+        read: read.collect(),
+        to_state: substitute(&transition.to_state, assignment),
+        write: write.collect(),
+        moves: transition.moves.clone(),
+    }
+}
+
+// Função para repetir o corpo de cada bloco for para os valores das suas variáveis,
+// mantendo a ordem da descrição. Devolve também os estados gerados, isto é, os nomes de
+// estado alterados pela troca das variáveis, na ordem em que aparecem.
+pub fn expand_templates(
+    transitions: &[TransitionPattern],
+    templates: &[Template],
+) -> (Vec<TransitionPattern>, Vec<String>) {
+    let mut expanded = Vec::new();
+    let mut states: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < transitions.len() {
+        // Um bloco sem transições não gera nada e não ocupa nenhuma posição
+        let starts_here = |template: &&Template| template.transitions.start == i && !template.transitions.is_empty();
+        let Some(template) = templates.iter().find(starts_here) else {
+            expanded.push(transitions[i].clone());
+            i += 1;
+            continue;
+        };
+        let first = expanded.len();
+        for assignment in template.assignments() {
+            for transition in &transitions[template.transitions.clone()] {
+                let instance = instantiate(transition, &assignment);
+                if expanded[first..].contains(&instance) {
+                    continue;
+                }
+                let generated = [(&transition.from_state, &instance.from_state), (&transition.to_state, &instance.to_state)];
+                for (written, state) in generated {
+                    if written != state && !states.contains(state) {
+                        states.push(state.clone());
+                    }
+                }
+                expanded.push(instance);
+            }
+        }
+        i = template.transitions.end;
+    }
+    (expanded, states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParseErrorKind;
+    use crate::{parse_description, Move};
+
+    const HEADER: &str = "Q = {q0, back}\nSigma = {a, b}\nGamma = {a, b, B}\nq0 = q0\nF = {back}\ndelta:\n";
+
+    fn transition(from_state: &str, read: &str, to_state: &str, write: &str) -> TransitionPattern {
+        TransitionPattern {
+            from_state: from_state.to_string(),
+            read: vec![ReadPattern::Symbol(read.to_string())],
+            to_state: to_state.to_string(),
+            write: vec![WritePattern::Symbol(write.to_string())],
+            moves: vec![Move::Right],
+        }
+    }
+
+    fn template(bindings: &[(&str, &[&str])], transitions: Range<usize>) -> Template {
+        let bindings = bindings
+            .iter()
+            .map(|(variable, values)| (variable.to_string(), values.iter().map(|value| value.to_string()).collect()))
+            .collect();
+        Template { bindings, transitions }
+    }
+
+    #[test]
+    fn body_is_repeated_for_each_value() {
+        let transitions = [
+            transition("q0", "B", "q0", "B"),
+            transition("q0", "x", "carry[x]", "B"),
+            transition("carry[x]", "B", "back", "x"),
+            transition("back", "a", "back", "a"),
+        ];
+        let (expanded, states) = expand_templates(&transitions, &[template(&[("x", &["a", "b"])], 1..3)]);
+        assert_eq!(
+            expanded,
+            [
+                transition("q0", "B", "q0", "B"),
+                transition("q0", "a", "carry[a]", "B"),
+                transition("carry[a]", "B", "back", "a"),
+                transition("q0", "b", "carry[b]", "B"),
+                transition("carry[b]", "B", "back", "b"),
+                transition("back", "a", "back", "a"),
+            ]
+        );
+        assert_eq!(states, ["carry[a]", "carry[b]"]);
+    }
+
+    #[test]
+    fn first_variable_varies_slowest() {
+        let transitions = [transition("q[x][y]", "x", "q[y]", "y")];
+        let templates = [template(&[("x", &["a", "b"]), ("y", &["a", "b"])], 0..1)];
+        let (expanded, states) = expand_templates(&transitions, &templates);
+        let from: Vec<&str> = expanded.iter().map(|transition| transition.from_state.as_str()).collect();
+        assert_eq!(from, ["q[a][a]", "q[a][b]", "q[b][a]", "q[b][b]"]);
+        assert_eq!(states, ["q[a][a]", "q[a]", "q[a][b]", "q[b]", "q[b][a]", "q[b][b]"]);
+    }
+
+    #[test]
+    fn repeated_instances_are_dropped_and_empty_blocks_ignored() {
+        let transitions = [transition("q0", "a", "back", "a"), transition("q0", "b", "back", "b")];
+        let templates = [template(&[("x", &["a", "b"])], 0..0), template(&[("x", &["a", "b"])], 0..1)];
+        let (expanded, states) = expand_templates(&transitions, &templates);
+        assert_eq!(expanded, transitions);
+        assert!(states.is_empty());
+    }
+
+    #[test]
+    fn names_are_replaced_only_as_whole_names_or_in_brackets() {
+        let transitions = [transition("xq", "x", "q[x]x", "[x]")];
+        let (expanded, _) = expand_templates(&transitions, &[template(&[("x", &["a"])], 0..1)]);
+        assert_eq!(expanded, [transition("xq", "a", "q[a]x", "[a]")]);
+    }
+
+    #[test]
+    fn generated_states_join_q() {
+        let source = format!(
+            "{}(q0, B) -> (q0, B, R)\nfor x in {{a, b}} {{\n(q0, x) -> (carry[x], B, R)\n\
+             (carry[x], B) -> (back, x, L)\n}}\n",
+            HEADER
+        );
+        let tm = parse_description(&source, "test.txt").unwrap();
+        assert_eq!(tm.states, ["q0", "back", "carry[a]", "carry[b]"]);
+        assert_eq!(tm.transitions.len(), 5);
+    }
+
+    #[test]
+    fn template_errors_point_at_the_variable_or_block() {
+        let errors = |body: &str| -> Vec<(usize, usize, ParseErrorKind)> {
+            let errors = parse_description(&format!("{}{}", HEADER, body), "test.txt").unwrap_err();
+            errors.into_iter().map(|error| (error.line, error.column, error.kind)).collect()
+        };
+        assert_eq!(
+            errors("for x in {} {\n(q0, x) -> (q0, x, R)\n}\n"),
+            vec![(7, 5, ParseErrorKind::EmptyTemplateValues { variable: "x".to_string() })]
+        );
+        assert_eq!(
+            errors("for x in {a}, x in {b} {\n(q0, x) -> (q0, x, R)\n}\n"),
+            vec![(7, 15, ParseErrorKind::DuplicateTemplateVariable { variable: "x".to_string() })]
+        );
+        assert_eq!(
+            errors("for x in {a} {\n    for y in {b} {\n(q0, x) -> (q0, y, R)\n}\n}\n"),
+            vec![(8, 5, ParseErrorKind::NestedTemplate)]
+        );
+    }
+}
@@ -0,0 +1,383 @@
+// Leitura e escrita de máquinas de Turing em TOML, com a estrutura descrita em
+// format.rs. Cada transição é uma tabela de uma lista de tabelas:
+//
+//     states = ["q0", "q1"]
+//     ...
+//
+//     [[transitions]]
+//     from = "q0"
+//     read = "a"
+//     to = "q1"
+//     write = "a"
+//     move = "R"
+//
+// O leitor aceita o subconjunto do TOML necessário para descrever uma máquina: chaves
+// simples ou entre aspas (sem pontos), strings básicas e literais de uma linha,
+// inteiros, booleanos, listas, tabelas embutidas, cabeçalhos [tabela] e [[lista]] e
+// comentários iniciados por #.
+
+use crate::format::{escape_string, machine_from_value, machine_to_value, Cursor, FormatError, Value};
+use crate::TuringMachine;
+
+// Tabela que recebe os pares chave = valor lidos: a raiz do documento, a tabela do
+// último cabeçalho [tabela] ou o último elemento da lista do cabeçalho [[lista]]
+enum Target {
+    Root,
+    Table(String),
+    ArrayTable(String),
+}
+
+struct TomlReader {
+    cursor: Cursor,
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+impl TomlReader {
+    fn skip_spaces(&mut self) {
+        while matches!(self.cursor.peek(), Some(' ' | '\t')) {
+            self.cursor.advance();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.cursor.peek() == Some('#') {
+            while self.cursor.peek().is_some_and(|c| c != '\n') {
+                self.cursor.advance();
+            }
+        }
+    }
+
+    // Ignora espaços, quebras de linha e comentários, como entre os elementos de uma lista
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.cursor.peek() {
+                Some('\n' | '\r') => {
+                    self.cursor.advance();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    // Depois de um par ou cabeçalho, só pode haver um comentário até o fim da linha
+    fn end_of_line(&mut self) -> Result<(), FormatError> {
+        self.skip_spaces();
+        self.skip_comment();
+        if self.cursor.peek() == Some('\r') {
+            self.cursor.advance();
+        }
+        match self.cursor.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.cursor.advance();
+                Ok(())
+            }
+            Some(c) => Err(self.cursor.error(format!("expected end of line, found '{}'", c))),
+        }
+    }
+
+    fn read_key(&mut self) -> Result<String, FormatError> {
+        let key = match self.cursor.peek() {
+            Some('"') => self.read_basic_string()?,
+            Some('\'') => self.read_literal_string()?,
+            Some(c) if is_bare_key_char(c) => {
+                let mut key = String::new();
+                while let Some(c) = self.cursor.peek().filter(|&c| is_bare_key_char(c)) {
+                    key.push(c);
+                    self.cursor.advance();
+                }
+                key
+            }
+            _ => return Err(self.cursor.error("expected a key")),
+        };
+        self.skip_spaces();
+        if self.cursor.peek() == Some('.') {
+            return Err(self.cursor.error("dotted keys are not supported"));
+        }
+        Ok(key)
+    }
+
+    fn read_basic_string(&mut self) -> Result<String, FormatError> {
+        if self.cursor.starts_with("\"\"\"") {
+            return Err(self.cursor.error("multi-line strings are not supported"));
+        }
+        self.cursor.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.cursor.advance() {
+                Some('"') => return Ok(text),
+                Some('\\') => {
+                    let escaped = match self.cursor.advance() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.read_unicode_escape(1)?,
+                        Some('U') => self.read_unicode_escape(2)?,
+                        _ => return Err(self.cursor.error("invalid escape sequence")),
+                    };
+                    text.push(escaped);
+                }
+                Some('\n') | None => return Err(self.cursor.error("unterminated string")),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    // Escape \uXXXX (um grupo de quatro dígitos) ou \UXXXXXXXX (dois grupos)
+    fn read_unicode_escape(&mut self, groups: usize) -> Result<char, FormatError> {
+        let mut code = 0;
+        for _ in 0..groups {
+            code = (code << 16) | self.cursor.read_hex4()?;
+        }
+        char::from_u32(code).ok_or_else(|| self.cursor.error("invalid unicode escape"))
+    }
+
+    fn read_literal_string(&mut self) -> Result<String, FormatError> {
+        if self.cursor.starts_with("'''") {
+            return Err(self.cursor.error("multi-line strings are not supported"));
+        }
+        self.cursor.expect('\'')?;
+        let mut text = String::new();
+        loop {
+            match self.cursor.advance() {
+                Some('\'') => return Ok(text),
+                Some('\n') | None => return Err(self.cursor.error("unterminated string")),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn read_value(&mut self) -> Result<Value, FormatError> {
+        match self.cursor.peek() {
+            Some('"') => Ok(Value::String(self.read_basic_string()?)),
+            Some('\'') => Ok(Value::String(self.read_literal_string()?)),
+            Some('[') => self.read_array(),
+            Some('{') => self.read_inline_table(),
+            Some('t') if self.cursor.consume("true") => Ok(Value::Bool(true)),
+            Some('f') if self.cursor.consume("false") => Ok(Value::Bool(false)),
+            Some(c) if c == '+' || c == '-' || c.is_ascii_digit() => Ok(Value::Integer(self.cursor.read_integer(true)?)),
+            Some(c) => Err(self.cursor.error(format!("expected a value, found '{}'", c))),
+            None => Err(self.cursor.error("expected a value, found end of file")),
+        }
+    }
+
+    fn read_array(&mut self) -> Result<Value, FormatError> {
+        self.cursor.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.cursor.peek() == Some(']') {
+                self.cursor.advance();
+                return Ok(Value::Array(items));
+            }
+            items.push(self.read_value()?);
+            self.skip_blank();
+            match self.cursor.advance() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.cursor.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn read_inline_table(&mut self) -> Result<Value, FormatError> {
+        self.cursor.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_spaces();
+        if self.cursor.peek() == Some('}') {
+            self.cursor.advance();
+            return Ok(Value::Table(entries));
+        }
+        loop {
+            self.skip_spaces();
+            let location = self.cursor.location();
+            let (key, value) = self.read_pair()?;
+            insert(&mut entries, key, value).map_err(|message| syntax_error(location, message))?;
+            self.skip_spaces();
+            match self.cursor.advance() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Table(entries)),
+                _ => return Err(self.cursor.error("expected ',' or '}' in inline table")),
+            }
+        }
+    }
+
+    // Lê um par chave = valor
+    fn read_pair(&mut self) -> Result<(String, Value), FormatError> {
+        let key = self.read_key()?;
+        self.skip_spaces();
+        self.cursor.expect('=')?;
+        self.skip_spaces();
+        let value = self.read_value()?;
+        Ok((key, value))
+    }
+
+    // Lê um cabeçalho [tabela] ou [[lista]], já criando a tabela correspondente
+    fn read_header(&mut self, root: &mut Vec<(String, Value)>) -> Result<Target, FormatError> {
+        let array = self.cursor.starts_with("[[");
+        self.cursor.expect('[')?;
+        if array {
+            self.cursor.expect('[')?;
+        }
+        self.skip_spaces();
+        let key = self.read_key()?;
+        self.cursor.expect(']')?;
+        if array {
+            self.cursor.expect(']')?;
+        }
+
+        let existing = root.iter_mut().find(|(name, _)| *name == key).map(|(_, value)| value);
+        match (array, existing) {
+            (false, None) => root.push((key.clone(), Value::Table(Vec::new()))),
+            (true, None) => root.push((key.clone(), Value::Array(vec![Value::Table(Vec::new())]))),
+            (true, Some(Value::Array(items))) if items.iter().all(|item| matches!(item, Value::Table(_))) => {
+                items.push(Value::Table(Vec::new()))
+            }
+            _ => return Err(self.cursor.error(format!("duplicate key '{}'", key))),
+        }
+        Ok(if array { Target::ArrayTable(key) } else { Target::Table(key) })
+    }
+}
+
+// Acrescenta um par à tabela, recusando chaves repetidas
+fn insert(entries: &mut Vec<(String, Value)>, key: String, value: Value) -> Result<(), String> {
+    if entries.iter().any(|(other, _)| *other == key) {
+        return Err(format!("duplicate key '{}'", key));
+    }
+    entries.push((key, value));
+    Ok(())
+}
+
+fn syntax_error((line, column): (usize, usize), message: String) -> FormatError {
+    FormatError::Syntax { line, column, message }
+}
+
+// Tabela do documento que recebe os próximos pares
+fn target_entries<'a>(root: &'a mut Vec<(String, Value)>, target: &Target) -> &'a mut Vec<(String, Value)> {
+    let (key, array) = match target {
+        Target::Root => return root,
+        Target::Table(key) => (key, false),
+        Target::ArrayTable(key) => (key, true),
+    };
+    let value = root.iter_mut().find(|(name, _)| name == key).map(|(_, value)| value).expect("header creates the table");
+    match value {
+        Value::Table(entries) if !array => entries,
+        Value::Array(items) => match items.last_mut() {
+            Some(Value::Table(entries)) => entries,
+            _ => unreachable!("header creates the table"),
+        },
+        _ => unreachable!("header creates the table"),
+    }
+}
+
+// Função para ler um documento TOML
+pub fn parse_value(source: &str) -> Result<Value, FormatError> {
+    let mut reader = TomlReader { cursor: Cursor::new(source) };
+    let mut root = Vec::new();
+    let mut target = Target::Root;
+    loop {
+        reader.skip_blank();
+        match reader.cursor.peek() {
+            None => return Ok(Value::Table(root)),
+            Some('[') => target = reader.read_header(&mut root)?,
+            Some(_) => {
+                let location = reader.cursor.location();
+                let (key, value) = reader.read_pair()?;
+                insert(target_entries(&mut root, &target), key, value)
+                    .map_err(|message| syntax_error(location, message))?;
+            }
+        }
+        reader.end_of_line()?;
+    }
+}
+
+// Função para ler uma máquina de Turing de um documento TOML
+pub fn parse_toml(source: &str) -> Result<TuringMachine, FormatError> {
+    machine_from_value(&parse_value(source)?)
+}
+
+// Chave simples quando possível, ou entre aspas
+fn write_key(output: &mut String, key: &str) {
+    if !key.is_empty() && key.chars().all(is_bare_key_char) {
+        output.push_str(key);
+    } else {
+        output.push_str(&escape_string(key));
+    }
+}
+
+// Escreve o valor em uma única linha, com as tabelas como tabelas embutidas
+fn write_inline(output: &mut String, value: &Value) {
+    match value {
+        Value::Null => unreachable!("TOML has no null value"),
+        Value::Bool(value) => output.push_str(&value.to_string()),
+        Value::Integer(value) => output.push_str(&value.to_string()),
+        Value::String(text) => output.push_str(&escape_string(text)),
+        Value::Array(items) => {
+            output.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_inline(output, item);
+            }
+            output.push(']');
+        }
+        Value::Table(entries) => {
+            output.push_str("{ ");
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_key(output, key);
+                output.push_str(" = ");
+                write_inline(output, value);
+            }
+            output.push_str(if entries.is_empty() { "}" } else { " }" });
+        }
+    }
+}
+
+fn write_pairs(output: &mut String, entries: &[(String, Value)]) {
+    for (key, value) in entries {
+        write_key(output, key);
+        output.push_str(" = ");
+        write_inline(output, value);
+        output.push('\n');
+    }
+}
+
+// Listas não vazias formadas apenas por tabelas viram seções [[lista]]
+fn is_array_of_tables(value: &Value) -> bool {
+    matches!(value, Value::Array(items) if !items.is_empty() && items.iter().all(|item| matches!(item, Value::Table(_))))
+}
+
+// Função para exportar uma máquina de Turing em TOML
+pub fn to_toml(tm: &TuringMachine) -> String {
+    let Value::Table(entries) = machine_to_value(tm) else {
+        unreachable!("a machine is a table");
+    };
+    let (sections, pairs): (Vec<_>, Vec<_>) = entries.into_iter().partition(|(_, value)| is_array_of_tables(value));
+
+    let mut output = String::new();
+    write_pairs(&mut output, &pairs);
+    for (key, value) in &sections {
+        let Value::Array(items) = value else { continue };
+        for item in items {
+            let Value::Table(entries) = item else { continue };
+            output.push_str("\n[[");
+            write_key(&mut output, key);
+            output.push_str("]]\n");
+            write_pairs(&mut output, entries);
+        }
+    }
+    output
+}
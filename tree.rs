@@ -0,0 +1,302 @@
+// Árvore de computação de uma máquina não determinística, com todas as configurações
+// geradas durante a exploração. Cada configuração guarda a transição usada para chegar
+// a ela a partir da configuração pai e, com a detecção de laços, as transições que
+// levam de volta a configurações já geradas. A árvore pode ser exportada em Graphviz DOT
+// ou como texto indentado, opcionalmente até uma profundidade máxima.
+
+use std::fmt::Write;
+
+use crate::diagram::escape_dot;
+use crate::Transition;
+
+// Situação de uma configuração na exploração
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeStatus {
+    // Folha que aceita a palavra
+    Accepting,
+    // Folha que rejeita a palavra: estado de rejeição ou nenhuma transição possível
+    Rejecting,
+    // Configuração cujas transições foram exploradas
+    Expanded,
+    // Configuração gerada, mas não avaliada por causa de algum limite ou porque outra
+    // ramificação aceitou antes
+    Unexplored,
+}
+
+impl NodeStatus {
+    // Marca exibida na árvore em texto
+    fn tag(self) -> &'static str {
+        match self {
+            NodeStatus::Accepting => " [aceita]",
+            NodeStatus::Rejecting => " [rejeita]",
+            NodeStatus::Expanded => "",
+            NodeStatus::Unexplored => " [não explorada]",
+        }
+    }
+
+    // Atributos do nó no formato DOT
+    fn dot_attributes(self) -> &'static str {
+        match self {
+            NodeStatus::Accepting => ", style=filled, fillcolor=palegreen",
+            NodeStatus::Rejecting => ", style=filled, fillcolor=lightpink",
+            NodeStatus::Expanded => "",
+            NodeStatus::Unexplored => ", style=dashed",
+        }
+    }
+}
+
+// Configuração da árvore, no formato das linhas do arquivo de saída
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub configuration: String,
+    // Índice da configuração pai e transição usada a partir dela
+    pub parent: Option<(usize, Transition)>,
+    pub children: Vec<usize>,
+    // Configurações já geradas às quais uma transição desta configuração leva de volta,
+    // com a transição usada
+    pub repeats: Vec<(usize, Transition)>,
+    pub depth: u64,
+    pub status: NodeStatus,
+}
+
+// Árvore de computação; a raiz é a configuração inicial, no índice 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputationTree {
+    pub nodes: Vec<TreeNode>,
+}
+
+impl ComputationTree {
+    // Número de configurações da subárvore com raiz em `index`, incluindo a própria
+    fn subtree_size(&self, index: usize) -> usize {
+        let mut size = 0;
+        let mut pending = vec![index];
+        while let Some(index) = pending.pop() {
+            size += 1;
+            pending.extend(&self.nodes[index].children);
+        }
+        size
+    }
+
+    // Configurações visíveis com a profundidade máxima dada, na ordem da exploração
+    fn visible(&self, max_depth: Option<u64>) -> impl Iterator<Item = (usize, &TreeNode)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(move |(_, node)| max_depth.is_none_or(|max_depth| node.depth <= max_depth))
+    }
+
+    // Se as transições a partir de `index` ficam visíveis com a profundidade máxima dada
+    fn shows_successors(&self, index: usize, max_depth: Option<u64>) -> bool {
+        max_depth.is_none_or(|max_depth| self.nodes[index].depth < max_depth)
+    }
+
+    // Quantas configurações abaixo de `index` ficam ocultas pela profundidade máxima
+    fn omitted(&self, index: usize, max_depth: Option<u64>) -> usize {
+        let node = &self.nodes[index];
+        if max_depth != Some(node.depth) {
+            return 0;
+        }
+        node.children.iter().map(|&child| self.subtree_size(child)).sum()
+    }
+
+    // Exporta a árvore no formato DOT do Graphviz. As folhas que aceitam ficam em verde,
+    // as que rejeitam em vermelho e as não exploradas tracejadas; as transições que levam
+    // a configurações repetidas são arestas tracejadas de volta a elas.
+    pub fn to_dot(&self, max_depth: Option<u64>) -> String {
+        let mut output = String::new();
+        output.push_str("digraph computation_tree {\n");
+        output.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+
+        for (index, node) in self.visible(max_depth) {
+            let label = escape_dot(&node.configuration);
+            writeln!(output, "    n{} [label=\"{}\"{}];", index, label, node.status.dot_attributes()).unwrap();
+            if let Some((parent, transition)) = &node.parent {
+                let label = escape_dot(&transition.to_string());
+                writeln!(output, "    n{} -> n{} [label=\"{}\"];", parent, index, label).unwrap();
+            }
+            if self.shows_successors(index, max_depth) {
+                for (target, transition) in &node.repeats {
+                    let label = escape_dot(&transition.to_string());
+                    let attributes = "style=dashed, constraint=false";
+                    writeln!(output, "    n{} -> n{} [label=\"{}\", {}];", index, target, label, attributes).unwrap();
+                }
+            }
+
+            let omitted = self.omitted(index, max_depth);
+            if omitted > 0 {
+                writeln!(
+                    output,
+                    "    n{}_omitted [label=\"... ({} configurações omitidas)\", shape=plaintext];",
+                    index, omitted
+                )
+                .unwrap();
+                writeln!(output, "    n{} -> n{}_omitted [style=dotted];", index, index).unwrap();
+            }
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    // Exporta a árvore como texto indentado, uma configuração por linha, precedida da
+    // transição usada para chegar a ela. Uma transição que leva a uma configuração
+    // repetida aparece depois dos filhos, marcada como repetida:
+    //
+    //     {q0}BabB
+    //     └── (q0, B) -> (q1, B, R): B{q1}abB
+    //         ├── (q1, a) -> (q1, a, R): Ba{q1}bB
+    //         ├── (q1, a) -> (q2, a, R): Ba{q2}bB [aceita]
+    //         └── (q1, a) -> (q0, a, L): {q0}BabB [repetida]
+    pub fn to_text(&self, max_depth: Option<u64>) -> String {
+        let mut output = String::new();
+        let Some(root) = self.nodes.first() else {
+            return output;
+        };
+        writeln!(output, "{}{}", root.configuration, root.status.tag()).unwrap();
+
+        // Percorre a árvore em profundidade com uma pilha explícita, já que uma única
+        // ramificação pode ter milhares de passos. Cada entrada guarda a configuração, o
+        // prefixo de indentação e se ela é o último filho do seu pai.
+        let mut pending = Vec::new();
+        let omitted = self.omitted(0, max_depth);
+        if omitted > 0 {
+            writeln!(output, "└── ... ({} configurações omitidas)", omitted).unwrap();
+        } else {
+            pending = self.children_entries(0, String::new(), max_depth);
+        }
+        while let Some((branch, prefix, last)) = pending.pop() {
+            let connector = if last { "└── " } else { "├── " };
+            let index = match branch {
+                Branch::Child(index) => index,
+                Branch::Repeat(from, repeat) => {
+                    let (target, transition) = &self.nodes[from].repeats[repeat];
+                    let configuration = &self.nodes[*target].configuration;
+                    writeln!(output, "{}{}{}: {} [repetida]", prefix, connector, transition, configuration).unwrap();
+                    continue;
+                }
+            };
+            let node = &self.nodes[index];
+            let transition = node.parent.as_ref().map(|(_, transition)| transition.to_string()).unwrap_or_default();
+            writeln!(output, "{}{}{}: {}{}", prefix, connector, transition, node.configuration, node.status.tag())
+                .unwrap();
+
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            let omitted = self.omitted(index, max_depth);
+            if omitted > 0 {
+                writeln!(output, "{}└── ... ({} configurações omitidas)", prefix, omitted).unwrap();
+            } else {
+                pending.extend(self.children_entries(index, prefix, max_depth));
+            }
+        }
+        output
+    }
+
+    // Entradas da pilha para os filhos de `index`, seguidos das transições que levam a
+    // configurações repetidas, em ordem inversa para que o primeiro filho seja escrito
+    // primeiro
+    fn children_entries(&self, index: usize, prefix: String, max_depth: Option<u64>) -> Vec<(Branch, String, bool)> {
+        let node = &self.nodes[index];
+        let children = node.children.iter().map(|&child| Branch::Child(child));
+        let repeats = if self.shows_successors(index, max_depth) { node.repeats.len() } else { 0 };
+        let repeats = (0..repeats).map(|repeat| Branch::Repeat(index, repeat));
+        let branches: Vec<Branch> = children.chain(repeats).collect();
+        let count = branches.len();
+        branches.into_iter().enumerate().rev().map(|(i, branch)| (branch, prefix.clone(), i + 1 == count)).collect()
+    }
+}
+
+// Linha da árvore em texto abaixo de uma configuração: um filho, ou a transição de
+// número dado entre as que levam de uma configuração a configurações repetidas
+#[derive(Clone, Copy)]
+enum Branch {
+    Child(usize),
+    Repeat(usize, usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    fn transition(from: &str, read: &str, to: &str, write: &str, direction: Move) -> Transition {
+        Transition {
+            from_state: from.to_string(),
+            read_symbols: vec![read.to_string()],
+            to_state: to.to_string(),
+            write_symbols: vec![write.to_string()],
+            moves: vec![direction],
+        }
+    }
+
+    // A árvore do exemplo de `to_text`, com a primeira folha ainda não explorada
+    fn tree() -> ComputationTree {
+        let node = |configuration: &str, parent, depth, status| TreeNode {
+            configuration: configuration.to_string(),
+            parent,
+            children: Vec::new(),
+            repeats: Vec::new(),
+            depth,
+            status,
+        };
+        let mut nodes = vec![
+            node("{q0}BabB", None, 0, NodeStatus::Expanded),
+            node("B{q1}abB", Some((0, transition("q0", "B", "q1", "B", Move::Right))), 1, NodeStatus::Expanded),
+            node("Ba{q1}bB", Some((1, transition("q1", "a", "q1", "a", Move::Right))), 2, NodeStatus::Unexplored),
+            node("Ba{q2}bB", Some((1, transition("q1", "a", "q2", "a", Move::Right))), 2, NodeStatus::Accepting),
+        ];
+        nodes[0].children = vec![1];
+        nodes[1].children = vec![2, 3];
+        nodes[1].repeats = vec![(0, transition("q1", "a", "q0", "a", Move::Left))];
+        ComputationTree { nodes }
+    }
+
+    #[test]
+    fn text_lists_repeated_configurations_after_the_children() {
+        let expected = "{q0}BabB\n\
+                        └── (q0, B) -> (q1, B, R): B{q1}abB\n    \
+                            ├── (q1, a) -> (q1, a, R): Ba{q1}bB [não explorada]\n    \
+                            ├── (q1, a) -> (q2, a, R): Ba{q2}bB [aceita]\n    \
+                            └── (q1, a) -> (q0, a, L): {q0}BabB [repetida]\n";
+        assert_eq!(tree().to_text(None), expected);
+    }
+
+    #[test]
+    fn text_summarizes_configurations_below_the_maximum_depth() {
+        let expected = "{q0}BabB\n\
+                        └── (q0, B) -> (q1, B, R): B{q1}abB\n    \
+                            └── ... (2 configurações omitidas)\n";
+        assert_eq!(tree().to_text(Some(1)), expected);
+        assert_eq!(tree().to_text(Some(0)), "{q0}BabB\n└── ... (3 configurações omitidas)\n");
+    }
+
+    #[test]
+    fn dot_draws_repeated_configurations_as_back_edges() {
+        let expected = r#"digraph computation_tree {
+    node [shape=box, fontname="monospace"];
+    n0 [label="{q0}BabB"];
+    n1 [label="B{q1}abB"];
+    n0 -> n1 [label="(q0, B) -> (q1, B, R)"];
+    n1 -> n0 [label="(q1, a) -> (q0, a, L)", style=dashed, constraint=false];
+    n2 [label="Ba{q1}bB", style=dashed];
+    n1 -> n2 [label="(q1, a) -> (q1, a, R)"];
+    n3 [label="Ba{q2}bB", style=filled, fillcolor=palegreen];
+    n1 -> n3 [label="(q1, a) -> (q2, a, R)"];
+}
+"#;
+        assert_eq!(tree().to_dot(None), expected);
+    }
+
+    #[test]
+    fn dot_omits_configurations_below_the_maximum_depth() {
+        let expected = r#"digraph computation_tree {
+    node [shape=box, fontname="monospace"];
+    n0 [label="{q0}BabB"];
+    n1 [label="B{q1}abB"];
+    n0 -> n1 [label="(q0, B) -> (q1, B, R)"];
+    n1_omitted [label="... (2 configurações omitidas)", shape=plaintext];
+    n1 -> n1_omitted [style=dotted];
+}
+"#;
+        assert_eq!(tree().to_dot(Some(1)), expected);
+    }
+}
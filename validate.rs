@@ -0,0 +1,338 @@
+// Validação estática da sêxtupla (Q, Σ, Γ, δ, q0, F) de uma máquina de Turing, feita
+// sem executá-la. Complementa o analisador, que verifica apenas a sintaxe da descrição.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::symbol::split_input_word;
+use crate::TuringMachine;
+
+// Problemas que podem ser encontrados na sêxtupla
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    UnknownState { transition: String, state: String },
+    UnknownSymbol { transition: String, symbol: String },
+    InputSymbolNotInTapeAlphabet { symbol: String },
+    BlankInInputAlphabet { blank: String },
+    UnknownInitialState { state: String },
+    UnknownAcceptState { state: String },
+    UnknownRejectState { state: String },
+    AcceptAndRejectState { state: String },
+    Nondeterministic { state: String, symbols: Vec<String>, count: usize },
+}
+
+// Símbolos lidos por uma transição: 'a' com uma fita, (a, B) com mais de uma
+fn format_read_symbols(symbols: &[String]) -> String {
+    match symbols {
+        [symbol] => format!("'{}'", symbol),
+        _ => format!("({})", symbols.join(", ")),
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::UnknownState { transition, state } => {
+                write!(f, "transition {} uses state '{}', which is not in Q", transition, state)
+            }
+            ValidationError::UnknownSymbol { transition, symbol } => {
+                write!(f, "transition {} uses symbol '{}', which is not in Gamma", transition, symbol)
+            }
+            ValidationError::InputSymbolNotInTapeAlphabet { symbol } => {
+                write!(f, "input symbol '{}' is not in the tape alphabet Gamma", symbol)
+            }
+            ValidationError::BlankInInputAlphabet { blank } => {
+                write!(f, "the blank symbol '{}' cannot be part of the input alphabet Sigma", blank)
+            }
+            ValidationError::UnknownInitialState { state } => {
+                write!(f, "initial state '{}' is not in Q", state)
+            }
+            ValidationError::UnknownAcceptState { state } => {
+                write!(f, "final state '{}' is not in Q", state)
+            }
+            ValidationError::UnknownRejectState { state } => {
+                write!(f, "reject state '{}' is not in Q", state)
+            }
+            ValidationError::AcceptAndRejectState { state } => {
+                write!(f, "state '{}' is both a final and a reject state", state)
+            }
+            ValidationError::Nondeterministic { state, symbols, count } => write!(
+                f,
+                "{} transitions for state '{}' reading {}; the machine is not deterministic",
+                count,
+                state,
+                format_read_symbols(symbols)
+            ),
+        }
+    }
+}
+
+// Função para validar uma máquina de Turing, retornando todos os problemas encontrados
+pub fn validate(tm: &TuringMachine) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let states: HashSet<&str> = tm.states.iter().map(String::as_str).collect();
+    let tape_alphabet: HashSet<&str> = tm.tape_alphabet.iter().map(String::as_str).collect();
+
+    for symbol in &tm.alphabet {
+        if !tape_alphabet.contains(symbol.as_str()) {
+            errors.push(ValidationError::InputSymbolNotInTapeAlphabet { symbol: symbol.clone() });
+        }
+    }
+    // Os leitores de descrição recusam um branco em Σ, mas uma máquina montada pela
+    // biblioteca pode tê-lo
+    if tm.alphabet.contains(&tm.blank) {
+        errors.push(ValidationError::BlankInInputAlphabet { blank: tm.blank.clone() });
+    }
+
+    if !states.contains(tm.initial_state.as_str()) {
+        errors.push(ValidationError::UnknownInitialState { state: tm.initial_state.clone() });
+    }
+    for state in &tm.accept_states {
+        if !states.contains(state.as_str()) {
+            errors.push(ValidationError::UnknownAcceptState { state: state.clone() });
+        }
+    }
+    for state in &tm.reject_states {
+        if !states.contains(state.as_str()) {
+            errors.push(ValidationError::UnknownRejectState { state: state.clone() });
+        }
+        if tm.accept_states.contains(state) {
+            errors.push(ValidationError::AcceptAndRejectState { state: state.clone() });
+        }
+    }
+
+    for transition in &tm.transitions {
+        for (i, state) in [&transition.from_state, &transition.to_state].into_iter().enumerate() {
+            // Um estado desconhecido repetido na mesma transição é informado uma só vez
+            let repeated = i == 1 && transition.to_state == transition.from_state;
+            if !repeated && !states.contains(state.as_str()) {
+                errors.push(ValidationError::UnknownState {
+                    transition: transition.to_string(),
+                    state: state.clone(),
+                });
+            }
+        }
+        // Um símbolo desconhecido também é informado uma só vez por transição
+        let mut reported = Vec::new();
+        for symbol in transition.read_symbols.iter().chain(&transition.write_symbols) {
+            if !reported.contains(&symbol) && !tape_alphabet.contains(symbol.as_str()) {
+                reported.push(symbol);
+                errors.push(ValidationError::UnknownSymbol {
+                    transition: transition.to_string(),
+                    symbol: symbol.clone(),
+                });
+            }
+        }
+    }
+
+    // Conta as transições de cada par (estado, símbolos lidos), mantendo a ordem em que
+    // os pares aparecem na descrição
+    let mut counts: HashMap<(&str, &[String]), usize> = HashMap::new();
+    let mut pairs = Vec::new();
+    for transition in &tm.transitions {
+        let pair = (transition.from_state.as_str(), transition.read_symbols.as_slice());
+        let count = counts.entry(pair).or_insert(0);
+        if *count == 0 {
+            pairs.push(pair);
+        }
+        *count += 1;
+    }
+    for (state, symbols) in pairs {
+        let count = counts[&(state, symbols)];
+        if count > 1 {
+            errors.push(ValidationError::Nondeterministic {
+                state: state.to_string(),
+                symbols: symbols.to_vec(),
+                count,
+            });
+        }
+    }
+
+    errors
+}
+
+// Símbolo da palavra de entrada que não pertence ao alfabeto de entrada Σ ou, quando a
+// palavra pode ser dividida de mais de uma forma, o primeiro ponto em que as divisões
+// diferem, com os dois símbolos de Σ que começam ali. A posição é a coluna, em
+// caracteres e a partir de 1, em que o símbolo começa na palavra.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidInputSymbol {
+    pub position: usize,
+    pub symbol: String,
+    pub ambiguous_with: Option<String>,
+}
+
+impl fmt::Display for InvalidInputSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.ambiguous_with {
+            None => write!(
+                f,
+                "symbol '{}' at position {} of the input word is not in the input alphabet Sigma",
+                self.symbol, self.position
+            ),
+            Some(other) => write!(
+                f,
+                "the input word can be split in more than one way at position {}, starting with '{}' or \
+                 '{}'; use --separator to separate its symbols",
+                self.position, self.symbol, other
+            ),
+        }
+    }
+}
+
+// Função para dividir a palavra de entrada em símbolos, com o separador dado ou pelos
+// símbolos de Σ, e validá-los, símbolo por símbolo, contra Σ. Devolve os símbolos da
+// palavra.
+pub fn validate_input_word(
+    tm: &TuringMachine,
+    input_word: &str,
+    separator: Option<&str>,
+) -> Result<Vec<String>, InvalidInputSymbol> {
+    let alphabet: HashSet<&str> = tm.alphabet.iter().map(String::as_str).collect();
+    let mut symbols = Vec::new();
+    for (position, symbol) in split_input_word(input_word, &tm.alphabet, separator)? {
+        if !alphabet.contains(symbol) {
+            return Err(InvalidInputSymbol { position, symbol: symbol.to_string(), ambiguous_with: None });
+        }
+        symbols.push(symbol.to_string());
+    }
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_description;
+
+    fn machine() -> TuringMachine {
+        let source = "Q = {q0, q1}\nSigma = {a, b}\nGamma = {a, b, X, B}\nq0 = q0\nF = {q1}\ndelta:\n\
+                      (q0, a) -> (q0, X, R)\n(q0, B) -> (q1, B, S)\n";
+        parse_description(source, "test.txt").unwrap()
+    }
+
+    #[test]
+    fn valid_machine_has_no_errors() {
+        assert_eq!(validate(&machine()), []);
+    }
+
+    #[test]
+    fn states_must_be_in_q() {
+        let mut tm = machine();
+        tm.transitions[0].from_state = "q2".to_string();
+        tm.transitions[0].to_state = "q2".to_string();
+        tm.transitions[1].to_state = "q3".to_string();
+        tm.initial_state = "q4".to_string();
+        tm.accept_states.push("q5".to_string());
+        tm.reject_states.push("q6".to_string());
+        let errors: Vec<String> = validate(&tm).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            [
+                "initial state 'q4' is not in Q",
+                "final state 'q5' is not in Q",
+                "reject state 'q6' is not in Q",
+                "transition (q2, a) -> (q2, X, R) uses state 'q2', which is not in Q",
+                "transition (q0, B) -> (q3, B, S) uses state 'q3', which is not in Q",
+            ]
+        );
+    }
+
+    #[test]
+    fn symbols_must_be_in_gamma() {
+        let mut tm = machine();
+        tm.transitions[0].read_symbols = vec!["c".to_string()];
+        tm.transitions[0].write_symbols = vec!["c".to_string()];
+        tm.transitions[1].write_symbols = vec!["Y".to_string()];
+        assert_eq!(
+            validate(&tm),
+            [
+                ValidationError::UnknownSymbol {
+                    transition: "(q0, c) -> (q0, c, R)".to_string(),
+                    symbol: "c".to_string()
+                },
+                ValidationError::UnknownSymbol {
+                    transition: "(q0, B) -> (q1, Y, S)".to_string(),
+                    symbol: "Y".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sigma_must_be_in_gamma_without_the_blank() {
+        let mut tm = machine();
+        tm.alphabet.push("c".to_string());
+        tm.alphabet.push("B".to_string());
+        assert_eq!(
+            validate(&tm),
+            [
+                ValidationError::InputSymbolNotInTapeAlphabet { symbol: "c".to_string() },
+                ValidationError::BlankInInputAlphabet { blank: "B".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn accept_and_reject_states_must_differ() {
+        let mut tm = machine();
+        tm.reject_states.push("q1".to_string());
+        assert_eq!(validate(&tm), [ValidationError::AcceptAndRejectState { state: "q1".to_string() }]);
+    }
+
+    #[test]
+    fn repeated_pairs_are_nondeterministic() {
+        let mut tm = machine();
+        let mut repeated = tm.transitions[0].clone();
+        repeated.to_state = "q1".to_string();
+        tm.transitions.push(repeated.clone());
+        tm.transitions.push(repeated);
+        let errors = validate(&tm);
+        assert_eq!(
+            errors,
+            [ValidationError::Nondeterministic { state: "q0".to_string(), symbols: vec!["a".to_string()], count: 3 }]
+        );
+        assert_eq!(errors[0].to_string(), "3 transitions for state 'q0' reading 'a'; the machine is not deterministic");
+    }
+
+    fn invalid(position: usize, symbol: &str) -> InvalidInputSymbol {
+        InvalidInputSymbol { position, symbol: symbol.to_string(), ambiguous_with: None }
+    }
+
+    #[test]
+    fn empty_word_is_in_sigma_star() {
+        let tm = machine();
+        assert_eq!(validate_input_word(&tm, "", None), Ok(Vec::new()));
+        assert_eq!(validate_input_word(&tm, "", Some(" ")), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn input_word_is_split_into_symbols_of_sigma() {
+        let tm = machine();
+        let symbols = |symbols: &[&str]| Ok(symbols.iter().map(ToString::to_string).collect());
+        assert_eq!(validate_input_word(&tm, "abba", None), symbols(&["a", "b", "b", "a"]));
+        assert_eq!(validate_input_word(&tm, "a b", Some(" ")), symbols(&["a", "b"]));
+    }
+
+    #[test]
+    fn symbols_outside_sigma_are_reported_with_their_position() {
+        let tm = machine();
+        // X e o branco pertencem a Γ, mas não a Σ
+        assert_eq!(validate_input_word(&tm, "aXb", None), Err(invalid(2, "X")));
+        assert_eq!(validate_input_word(&tm, "abB", None), Err(invalid(3, "B")));
+        assert_eq!(validate_input_word(&tm, "a?", None), Err(invalid(2, "?")));
+        assert_eq!(validate_input_word(&tm, "a b X", Some(" ")), Err(invalid(5, "X")));
+        assert_eq!(
+            invalid(2, "X").to_string(),
+            "symbol 'X' at position 2 of the input word is not in the input alphabet Sigma"
+        );
+    }
+
+    #[test]
+    fn positions_count_characters_of_longer_symbols() {
+        let mut tm = machine();
+        tm.alphabet = vec!["ab".to_string(), "c".to_string()];
+        tm.tape_alphabet.extend(["ab".to_string(), "c".to_string()]);
+        assert_eq!(validate_input_word(&tm, "abcab", None).map(|symbols| symbols.len()), Ok(3));
+        assert_eq!(validate_input_word(&tm, "abcd", None), Err(invalid(4, "d")));
+    }
+}